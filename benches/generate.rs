@@ -0,0 +1,32 @@
+//! Compare generating a long sequence with the samplers cached in the generator against building a
+//! sampler from the probabilities at every step.
+//!
+//! Run with `cargo bench --bench generate`.
+
+use std::{hint::black_box, time::Instant};
+
+use markov_chord::{parse_progression, ChordGenerator};
+use rand::{rngs::StdRng, SeedableRng};
+
+const NUMBER: usize = 100_000;
+
+fn main() {
+    let corpus = "C G Am Em F C F G7 | C G Am Em F C F G | C G Am Em F C Dm G7 | \
+                  Am F C G | Dm7 G7 CM7 Am7 | F G Em Am | Dm G C C";
+    let chords = parse_progression(corpus).unwrap();
+    let generator = ChordGenerator::with_smoothing(&chords, 0.1);
+
+    let start = Instant::now();
+    let cached = generator.generate_seeded(chords[0].clone(), NUMBER, 0).unwrap();
+    let cached_time = start.elapsed();
+
+    let start = Instant::now();
+    let mut iter = generator.iter(chords[0].clone(), StdRng::seed_from_u64(0));
+    let fresh: Vec<_> = (0..NUMBER).map_while(|_| iter.next_matching(|_| true)).collect();
+    let fresh_time = start.elapsed();
+
+    assert_eq!(black_box(cached), black_box(fresh));
+    println!("generate {NUMBER} chords");
+    println!("cached samplers: {cached_time:?}");
+    println!("fresh samplers:  {fresh_time:?}");
+}
@@ -0,0 +1,76 @@
+//! Compare the memory and training time of a dense and a sparse generator with a vocabulary of 300
+//! chords, where each chord is followed by only 2 chords.
+//!
+//! Run with `cargo bench --bench sparse`.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use markov_chord::{note::note_string, Chord, ChordGenerator};
+
+/// Allocator counting the bytes currently allocated.
+struct Counting;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const QUALITIES: [&str; 25] = [
+    "", "m", "7", "M7", "m7", "dim", "aug", "sus2", "sus4", "6", "m6", "9", "add9", "ø7", "13",
+    "m9", "M9", "11", "m11", "dim7", "aug7", "m13", "M13", "M11", "madd9",
+];
+
+/// Train a generator and return it with the bytes it allocated.
+fn measure(train: impl FnOnce() -> ChordGenerator) -> (ChordGenerator, usize) {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let generator = train();
+    (generator, ALLOCATED.load(Ordering::Relaxed) - before)
+}
+
+fn main() {
+    let chords: Vec<Chord> = (0..12u8)
+        .flat_map(|root| {
+            QUALITIES.map(|quality| {
+                Chord::try_from(format!("{}{}", note_string(root)[0], quality).as_str()).unwrap()
+            })
+        })
+        .collect();
+    let chord_seq: Vec<Chord> = chords
+        .iter()
+        .chain(chords.iter().step_by(2))
+        .cloned()
+        .collect();
+
+    let start = Instant::now();
+    let (dense, dense_bytes) = measure(|| ChordGenerator::new(&chord_seq));
+    let dense_time = start.elapsed();
+
+    let start = Instant::now();
+    let (sparse, sparse_bytes) = measure(|| ChordGenerator::new_sparse(&chord_seq));
+    let sparse_time = start.elapsed();
+
+    assert_eq!(
+        black_box(dense).generate_seeded(chords[0].clone(), 32, 0),
+        black_box(sparse).generate_seeded(chords[0].clone(), 32, 0)
+    );
+    println!("train {} chords", chords.len());
+    println!("dense:  {dense_bytes} bytes in {dense_time:?}");
+    println!("sparse: {sparse_bytes} bytes in {sparse_time:?}");
+}
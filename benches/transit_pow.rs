@@ -0,0 +1,50 @@
+//! Compare calculating powers of the transition matrix into one reused buffer against allocating a
+//! new buffer for every power, with the power cache disabled.
+//!
+//! Run with `cargo bench --bench transit_pow`, and with `--features rayon` to multiply the matrices
+//! in parallel.
+
+use std::{hint::black_box, time::Instant};
+
+use markov_chord::{note::note_string, Chord, ChordGenerator};
+use nalgebra::DMatrix;
+
+const QUALITIES: [&str; 10] = ["", "m", "7", "M7", "m7", "dim", "sus4", "9", "m9", "ø7"];
+const NUMBER: u32 = 20;
+const POWER: u32 = 37;
+
+fn main() {
+    let chords: Vec<Chord> = (0..12u8)
+        .flat_map(|root| {
+            QUALITIES.map(|quality| {
+                Chord::try_from(format!("{}{}", note_string(root)[0], quality).as_str()).unwrap()
+            })
+        })
+        .collect();
+    let mut generator = ChordGenerator::with_smoothing(&chords, 0.1);
+    generator.set_cache_capacity(0);
+
+    let start = Instant::now();
+    let mut out = DMatrix::zeros(0, 0);
+    for n in POWER..POWER + NUMBER {
+        generator.transit_pow_into(n, &mut out);
+        black_box(&out);
+    }
+    let reused_time = start.elapsed();
+
+    let start = Instant::now();
+    for n in POWER..POWER + NUMBER {
+        let mut fresh = DMatrix::zeros(0, 0);
+        generator.transit_pow_into(n, &mut fresh);
+        black_box(&fresh);
+    }
+    let fresh_time = start.elapsed();
+
+    println!(
+        "{NUMBER} powers of a {0}x{0} transition matrix, parallel: {1}",
+        chords.len(),
+        cfg!(feature = "rayon")
+    );
+    println!("reused buffer: {reused_time:?}");
+    println!("fresh buffers: {fresh_time:?}");
+}
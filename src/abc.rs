@@ -0,0 +1,103 @@
+use crate::{
+    chord::{Chord, ChordQuality},
+    key::Key,
+    rhythm,
+};
+
+/// Spell a chord name so that it only uses symbols understood by ABC guitar chord annotations, e.g.
+/// half-diminished chords are spelled as minor chords with a flat fifth.
+fn abc_chord_name(chord: &Chord, key: Key) -> String {
+    let root_name = key.note_name(chord.root());
+    let head = match chord.quality() {
+        ChordQuality::HalfDim => match chord.note_num() {
+            3 => format!("{}dim", root_name),
+            note_num => format!("{}m{}b5", root_name, note_num * 2 - 1),
+        },
+        ChordQuality::Aug => chord.head_with(root_name).replace("aug", "+"),
+        _ => chord.head_with(root_name),
+    };
+    chord.name_with_head(head, chord.bass().map(|bass| key.note_name(bass)))
+}
+
+/// Length of a rest of `sixteenths` sixteenth notes relative to the unit note length of a whole
+/// note, e.g. "" for a whole note and "3/8" for a dotted quarter note.
+fn abc_length(sixteenths: u32) -> String {
+    let divisor = num::integer::gcd(sixteenths, rhythm::BAR_SIXTEENTHS);
+    match (sixteenths / divisor, rhythm::BAR_SIXTEENTHS / divisor) {
+        (1, 1) => String::new(),
+        (numerator, 1) => numerator.to_string(),
+        (1, denominator) => format!("/{}", denominator),
+        (numerator, denominator) => format!("{}/{}", numerator, denominator),
+    }
+}
+
+/// Write the chord sequence as a minimal ABC tune in `key`. Each chord is a guitar chord annotation
+/// over a rest lasting `durations[i]` beats, or a whole bar if `durations` is shorter than `chords`.
+/// Durations are rounded to sixteenth notes, and chords are held over bar lines if needed.
+pub fn to_abc(chords: &[Chord], durations: &[f32], key: Key) -> String {
+    let mut ans = format!("X:1\nT:Markov Chord\nM:4/4\nL:1/1\nK:{}\n", key);
+    let bars: Vec<String> = rhythm::bars(chords, durations)
+        .iter()
+        .map(|bar| {
+            let rests: Vec<String> = bar
+                .iter()
+                .map(|&(chord, len)| match chord {
+                    Some(chord) => {
+                        format!("\"{}\"z{}", abc_chord_name(chord, key), abc_length(len))
+                    }
+                    None => format!("z{}", abc_length(len)),
+                })
+                .collect();
+            rests.join(" ")
+        })
+        .collect();
+    ans += &bars.join(" | ");
+    ans += " |]\n";
+    ans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_abc() {
+        let chords = [
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("G7").unwrap(),
+            Chord::try_from("Am").unwrap(),
+        ];
+        assert_eq!(
+            to_abc(&chords, &[], Key::major(3)),
+            "X:1\nT:Markov Chord\nM:4/4\nL:1/1\nK:C\n\"C\"z | \"G7\"z | \"Am\"z |]\n"
+        );
+        assert_eq!(
+            to_abc(&chords, &[2.0, 3.0, 1.5], Key::major(3)),
+            "X:1\nT:Markov Chord\nM:4/4\nL:1/1\nK:C\n\"C\"z/2 \"G7\"z/2 | z/4 \"Am\"z3/8 z3/8 |]\n"
+        );
+    }
+
+    #[test]
+    fn test_abc_chord_name() {
+        let key = Key::major(1);
+        let half_dim = Chord::try_from("Aø").unwrap();
+        assert_eq!(abc_chord_name(&half_dim, key), "Am7b5");
+        let aug = Chord::try_from("Ebaug").unwrap();
+        assert_eq!(abc_chord_name(&aug, key), "Eb+");
+
+        // half-diminished chords of every size are spelled from their size
+        let key = Key::major(3);
+        for (name, expected) in [
+            ("Cø5", "Cdim"),
+            ("Cø7", "Cm7b5"),
+            ("Cø9", "Cm9b5"),
+            ("Cø11", "Cm11b5"),
+            ("Cø13", "Cm13b5"),
+            ("Cøadd11/Gb", "Cm7b5add11/Gb"),
+            ("Caug7", "C+7"),
+        ] {
+            let chord = Chord::try_from(name).unwrap();
+            assert_eq!(abc_chord_name(&chord, key), expected);
+        }
+    }
+}
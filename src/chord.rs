@@ -1,16 +1,24 @@
 use std::{
+    cmp::Ordering,
     fmt::{Debug, Display},
     hash::Hash,
+    str::FromStr,
 };
 
-use num_derive::FromPrimitive;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 use crate::{
     error,
-    note::{consume_to_note, note_string, Note},
+    key::Key,
+    note::{
+        add_semitones, consume_to_note_with_convention, fifths_distance, midi_number, note_string,
+        note_string_with, AccidentalStyle, Note, NoteConvention,
+    },
 };
 
-#[derive(FromPrimitive, PartialEq, Eq, Debug, Clone, Copy)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ChordQuality {
     Maj,     // major
     Min,     // minor
@@ -18,17 +26,47 @@ pub enum ChordQuality {
     Aug,     // augmented
     Dim,     // diminished
     HalfDim, // half diminished
+    Sus2,    // suspended second
+    Sus4,    // suspended fourth
+    Power,   // power chord, i.e. root and fifth without third
 }
 
 impl ChordQuality {
+    /// All chord qualities.
+    pub const ALL: [ChordQuality; 9] = [
+        ChordQuality::Maj,
+        ChordQuality::Min,
+        ChordQuality::Dom,
+        ChordQuality::Aug,
+        ChordQuality::Dim,
+        ChordQuality::HalfDim,
+        ChordQuality::Sus2,
+        ChordQuality::Sus4,
+        ChordQuality::Power,
+    ];
+
     pub fn relative_pitch(&self) -> &'static [Note] {
         match self {
-            ChordQuality::Maj => &[0, 4, 7, 11, 14],
-            ChordQuality::Min => &[0, 3, 7, 10, 14],
-            ChordQuality::Dom => &[0, 4, 7, 10, 14],
-            ChordQuality::Aug => &[0, 4, 8, 12, 16],
-            ChordQuality::Dim => &[0, 3, 6, 9, 12],
-            ChordQuality::HalfDim => &[0, 3, 6, 10, 14],
+            ChordQuality::Maj => &[0, 4, 7, 11, 14, 17, 21],
+            ChordQuality::Min => &[0, 3, 7, 10, 14, 17, 21],
+            ChordQuality::Dom => &[0, 4, 7, 10, 14, 17, 21],
+            ChordQuality::Aug => &[0, 4, 8, 12, 16, 20, 24],
+            ChordQuality::Dim => &[0, 3, 6, 9, 12, 15, 18],
+            ChordQuality::HalfDim => &[0, 3, 6, 10, 14, 17, 21],
+            ChordQuality::Sus2 => &[0, 2, 7, 10, 14, 17, 21],
+            ChordQuality::Sus4 => &[0, 5, 7, 10, 14, 17, 21],
+            ChordQuality::Power => &[0, 7],
+        }
+    }
+
+    /// Scale degrees of the chord tones in `relative_pitch`, e.g. the second tone of a suspended
+    /// fourth chord is the fourth rather than the third.
+    pub(crate) fn degrees(&self) -> &'static [u8] {
+        match self {
+            ChordQuality::Sus2 => &[1, 2, 5, 7, 9, 11, 13],
+            ChordQuality::Sus4 => &[1, 4, 5, 7, 9, 11, 13],
+            ChordQuality::Power => &[1, 5],
+            _ => &[1, 3, 5, 7, 9, 11, 13],
         }
     }
 }
@@ -42,125 +80,801 @@ impl Display for ChordQuality {
             Self::Aug => "aug",
             Self::Dim => "dim",
             Self::HalfDim => "ø",
+            Self::Sus2 => "sus2",
+            Self::Sus4 => "sus4",
+            Self::Power => "5",
         })
     }
 }
 
+/// Harmonic function of a chord in a key.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub enum HarmonicFunction {
+    Tonic,       // I, iii and vi
+    Subdominant, // ii and IV
+    Dominant,    // V and vii
+}
+
 /// Defines a chord. A chord is defined from:
 /// - The root note.
 /// - The quality of chord (major, minor, augment, diminished, etc.).
 /// - The number of notes in the chord.
+/// - The added notes on top of the chord, e.g. Cadd9, C6.
+/// - The altered chord tones, e.g. C7b9, C7#5.
+/// - The omitted chord tones, e.g. C7(no5).
+/// - The bass note of a slash chord, e.g. C/E.
 ///
-/// The `Chord` struct does not support:
-/// - Chord with additional notes, e.g. Cadd6.
-/// - Chord with replaced notes, e.g. Csus2.
+/// Suspended chords (e.g. Csus2, Csus4) are treated as a chord quality.
 #[derive(PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Chord {
     root: Note,   // root note
     note_num: u8, // number of notes in the chord
     quality: ChordQuality,
+    added: Vec<u8>, // scale degrees of added notes
+    #[cfg_attr(feature = "serde", serde(default))]
+    altered: Vec<(u8, i8)>, // scale degrees of altered chord tones and their shift, e.g. (9, -1)
+    #[cfg_attr(feature = "serde", serde(default))]
+    omitted: Vec<u8>, // scale degrees of omitted chord tones, e.g. 5 in C7(no5)
+    bass: Option<Note>, // explicit bass note of slash chords
 }
 
 impl Chord {
-    /// List all notes of current chord in sequence. All notes are in modulo 12.
-    fn notes(&self) -> Vec<Note> {
+    /// Root note of the chord.
+    pub fn root(&self) -> Note {
+        self.root
+    }
+
+    /// Quality of the chord.
+    pub fn quality(&self) -> ChordQuality {
         self.quality
+    }
+
+    /// Number of notes in the chord, not counting added notes.
+    pub fn note_num(&self) -> u8 {
+        self.note_num
+    }
+
+    /// Scale degrees of the added notes of the chord.
+    pub fn added(&self) -> &[u8] {
+        &self.added
+    }
+
+    /// Altered chord tones as their scale degrees and the semitones they are raised by, sorted by
+    /// degree, e.g. [(5, 1), (9, -1)] for C7#5b9.
+    pub fn altered(&self) -> &[(u8, i8)] {
+        &self.altered
+    }
+
+    /// Scale degrees of the chord tones omitted from the chord, e.g. `[5]` for C7(no5).
+    pub fn omitted(&self) -> &[u8] {
+        &self.omitted
+    }
+
+    /// Bass note of the chord, if it is a slash chord.
+    pub fn bass(&self) -> Option<Note> {
+        self.bass
+    }
+
+    /// Identify the chord consisting of exactly the given notes, ignoring order and octaves.
+    ///
+    /// Every note is tried as the root in the given order, with every quality and chord size from
+    /// triads (or power chords) to 13th chords, so the first note is preferred as the root. If the
+    /// root is not the first note, the first note becomes the bass note, e.g. E G C gives C/E.
+    /// Returns `None` if no chord without added notes consists of the notes.
+    pub fn from_notes(notes: &[Note]) -> Option<Chord> {
+        let mut pitch_classes: Vec<Note> = notes.iter().map(|note| note % 12).collect();
+        pitch_classes.sort_unstable();
+        pitch_classes.dedup();
+        for &root in notes {
+            let root = root % 12;
+            for quality in ChordQuality::ALL {
+                for note_num in
+                    default_note_num(quality).min(3) as usize..=quality.relative_pitch().len()
+                {
+                    let mut chord_notes: Vec<Note> = quality.relative_pitch()[..note_num]
+                        .iter()
+                        .map(|rel| (root + rel) % 12)
+                        .collect();
+                    chord_notes.sort_unstable();
+                    chord_notes.dedup();
+                    if chord_notes == pitch_classes {
+                        return Some(Chord {
+                            root,
+                            note_num: note_num as u8,
+                            quality,
+                            added: Vec::new(),
+                            altered: Vec::new(),
+                            omitted: Vec::new(),
+                            bass: Some(notes[0] % 12).filter(|&bass| bass != root),
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Parse a chord written in roman numeral relative to `key`, e.g. "V7", "vi", "bVII", "vii°".
+    ///
+    /// Uppercase numerals are major chords and lowercase numerals are minor chords, unless the
+    /// quality is specified after the numeral. "°" after the numeral means a diminished chord. An
+    /// accidental before the numeral raises or lowers the root from the scale of the key.
+    pub fn try_from_roman(value: &str, key: Key) -> Result<Self, error::Error> {
+        let (shift, str_numeral) = match value.chars().next() {
+            Some(ch @ ('#' | '♯')) => (1, &value[ch.len_utf8()..]),
+            Some(ch @ ('b' | '♭')) => (11, &value[ch.len_utf8()..]),
+            _ => (0, value),
+        };
+        let numeral_len = str_numeral
+            .find(|ch| !matches!(ch, 'I' | 'V' | 'i' | 'v'))
+            .unwrap_or(str_numeral.len());
+        let (numeral, str_next) = str_numeral.split_at(numeral_len);
+        let degree = match numeral.to_uppercase().as_str() {
+            "I" => 0,
+            "II" => 1,
+            "III" => 2,
+            "IV" => 3,
+            "V" => 4,
+            "VI" => 5,
+            "VII" => 6,
+            _ => Err(error::Error::InvalidRomanNumeral(value.to_string()))?,
+        };
+        let major = if numeral == numeral.to_uppercase() {
+            true
+        } else if numeral == numeral.to_lowercase() {
+            false
+        } else {
+            Err(error::Error::InvalidRomanNumeral(value.to_string()))?
+        };
+        let root = (key.tonic() + key.scale_intervals()[degree] + shift) % 12;
+
+        // Translate the numeral into a chord name and parse it.
+        let quality_str = if let Some(str_next) = str_next.strip_prefix('°') {
+            format!("dim{}", str_next)
+        } else if major
+            || ["M", "m", "+", "aug", "o", "dim", "ø", "sus"]
+                .iter()
+                .any(|prefix| str_next.starts_with(prefix))
+        {
+            str_next.to_string()
+        } else {
+            format!("m{}", str_next)
+        };
+        Self::try_from(format!("{}{}", note_string(root)[0], quality_str).as_str())
+    }
+
+    /// Name of the chord with the root spelled in the way suitable for `key`, e.g. "Bb" rather than
+    /// "A#" in the key of F.
+    pub fn display_in_key(&self, key: Key) -> String {
+        self.name_with(
+            key.note_name(self.root),
+            self.bass.map(|bass| key.note_name(bass)),
+        )
+    }
+
+    /// Name of the chord spelled with sharps in `style` like `Display`, e.g. "A♯m7/G♯" in
+    /// `AccidentalStyle::Unicode`.
+    pub fn display_with(&self, style: AccidentalStyle) -> String {
+        self.name_with(
+            note_string_with(self.root, style)[0],
+            self.bass.map(|bass| note_string_with(bass, style)[0]),
+        )
+    }
+
+    /// Name of the chord with the given spelling of root note and bass note.
+    pub(crate) fn name_with(&self, root_name: &str, bass_name: Option<&str>) -> String {
+        self.name_with_head(self.head_with(root_name), bass_name)
+    }
+
+    /// Name of the root, quality and size of the chord with the given spelling of root note, e.g.
+    /// "Cm7" for Cm7b5/E.
+    pub(crate) fn head_with(&self, root_name: &str) -> String {
+        // The number is left out only for triads whose quality gives a triad by default, and for
+        // chords with added notes of the default size of their quality, e.g. "Cøadd11", since notes
+        // are only added to chords without a number, so that the name is always parsed back to the
+        // same chord.
+        let size = self.note_num * 2 - 1;
+        match self.quality {
+            ChordQuality::Power => format!("{}5", root_name),
+            _ if !self.added.is_empty()
+                && self.note_num > 3
+                && self.note_num == default_note_num(self.quality) =>
+            {
+                format!("{}{}", root_name, self.quality)
+            }
+            ChordQuality::Maj if self.note_num == 3 => root_name.to_string(),
+            _ if self.note_num == 3 && default_note_num(self.quality) == 3 => {
+                format!("{}{}", root_name, self.quality)
+            }
+            ChordQuality::Dom if self.note_num != 3 => format!("{}{}", root_name, size),
+            ChordQuality::Sus2 | ChordQuality::Sus4 => {
+                format!("{}{}{}", root_name, size, self.quality)
+            }
+            _ => format!("{}{}{}", root_name, self.quality, size),
+        }
+    }
+
+    /// Name of the chord from `head`, the name of its root, quality and size (see `head_with`),
+    /// followed by its added, altered and omitted notes and the given spelling of bass note.
+    pub(crate) fn name_with_head(&self, head: String, bass_name: Option<&str>) -> String {
+        let name = self.added.iter().fold(head, |name, degree| {
+            if *degree == 6 && self.note_num == 3 {
+                format!("{}6", name)
+            } else {
+                format!("{}add{}", name, degree)
+            }
+        });
+        let name = self.altered.iter().fold(name, |name, &(degree, shift)| {
+            format!("{}{}{}", name, if shift < 0 { "b" } else { "#" }, degree)
+        });
+        let name = self
+            .omitted
+            .iter()
+            .fold(name, |name, degree| format!("{}(no{})", name, degree));
+        match bass_name {
+            Some(bass_name) => format!("{}/{}", name, bass_name),
+            None => name,
+        }
+    }
+
+    /// Harmonic function of the chord in `key` given by the scale degree of its root, or `None` if
+    /// the chord has notes outside the scale of the key. Minor keys also accept the leading tone of
+    /// the harmonic minor scale, e.g. E7 is the dominant in A minor.
+    pub fn function(&self, key: Key) -> Option<HarmonicFunction> {
+        let mut scale = key.scale();
+        if !key.is_major() {
+            scale.push((key.tonic() + 11) % 12);
+        }
+        if !self.notes().iter().all(|note| scale.contains(note)) {
+            return None;
+        }
+        let interval = (self.root + 12 - key.tonic()) % 12;
+        let degree = key
+            .scale_intervals()
+            .iter()
+            .position(|&rel| rel == interval)
+            .or((interval == 11).then_some(6))?;
+        Some(match degree {
+            0 | 2 | 5 => HarmonicFunction::Tonic,
+            1 | 3 => HarmonicFunction::Subdominant,
+            _ => HarmonicFunction::Dominant,
+        })
+    }
+
+    /// Transpose the chord by `semitones`, upward if positive and downward if negative. The bass
+    /// note is transposed together with the root.
+    pub fn transpose(&self, semitones: i8) -> Chord {
+        Self {
+            root: add_semitones(self.root, semitones),
+            bass: self.bass.map(|bass| add_semitones(bass, semitones)),
+            ..self.clone()
+        }
+    }
+
+    /// Index of the chord tone with scale degree `degree` in `relative_pitch` of the quality, or
+    /// `None` if the chord has no such tone, e.g. the third of a suspended or power chord.
+    fn tone_index(&self, degree: u8) -> Option<usize> {
+        self.quality
+            .degrees()
+            .iter()
+            .take(self.note_num as usize)
+            .position(|&other| other == degree)
+    }
+
+    /// Intervals in semitones above the root of the chord tones that are not omitted, followed by
+    /// the altered tensions above the chord and the added notes.
+    fn relative_pitch(&self) -> Vec<Note> {
+        let mut tones: Vec<Option<Note>> = self
+            .quality
             .relative_pitch()
             .iter()
             .take(self.note_num as usize)
-            .map(|rel| (self.root + rel) % 12)
+            .map(|&rel| Some(rel))
+            .collect();
+        for &(degree, shift) in &self.altered {
+            let altered = (altered_interval(degree) as i8 + shift) as Note;
+            match self.tone_index(degree) {
+                Some(index) => tones[index] = Some(altered),
+                None => tones.push(Some(altered)),
+            }
+        }
+        for &degree in &self.omitted {
+            if let Some(index) = self.tone_index(degree) {
+                tones[index] = None;
+            }
+        }
+        tones
+            .into_iter()
+            .flatten()
+            .chain(
+                self.added
+                    .iter()
+                    .filter_map(|&degree| added_interval(degree)),
+            )
             .collect()
     }
+
+    /// List all notes of current chord in sequence. All notes are in modulo 12.
+    ///
+    /// If the chord has a bass note, the bass note is listed first.
+    pub fn notes(&self) -> Vec<Note> {
+        let mut notes: Vec<Note> = self
+            .relative_pitch()
+            .into_iter()
+            .map(|rel| add_semitones(self.root, rel as i8))
+            .collect();
+        if let Some(bass) = self.bass {
+            notes.retain(|&note| note != bass);
+            notes.insert(0, bass);
+        }
+        notes
+    }
+
+    /// Ascending intervals in semitones between successive chord tones in root position, e.g. [4, 3]
+    /// for a major triad. Added notes are placed at their interval above the root, and the bass
+    /// note of a slash chord is ignored.
+    pub fn intervals(&self) -> Vec<u8> {
+        let mut pitches = self.relative_pitch();
+        pitches.sort_unstable();
+        pitches.dedup();
+        pitches.windows(2).map(|pair| pair[1] - pair[0]).collect()
+    }
+
+    /// Whether the two chords consist of the same pitch classes, regardless of spelling, order,
+    /// or how the chords are written, e.g. C6 and Am7/C.
+    ///
+    /// Chords whose roots are enharmonic spellings of the same note, e.g. C# and Db, are already
+    /// equal since notes are stored in modulo 12.
+    pub fn is_enharmonic(&self, other: &Chord) -> bool {
+        let mut notes = self.notes();
+        let mut other_notes = other.notes();
+        notes.sort_unstable();
+        notes.dedup();
+        other_notes.sort_unstable();
+        other_notes.dedup();
+        notes == other_notes
+    }
+
+    /// Harmonic distance between two chords, i.e. the number of pitch classes in only one of the
+    /// chords, plus the distance between their roots on the circle of fifths. The distance of a
+    /// chord to itself is 0, and chords sharing most notes with nearby roots are close, e.g. C and
+    /// Am are at distance 5 while C and F# are at distance 12.
+    pub fn distance(&self, other: &Chord) -> u32 {
+        let notes = self.notes();
+        let other_notes = other.notes();
+        let difference = (0..12)
+            .filter(|note| notes.contains(note) != other_notes.contains(note))
+            .count() as u32;
+        difference + fifths_distance(self.root, other.root) as u32
+    }
+
+    /// List all notes of current chord in sequence like `notes()`, voiced as the inversion given by
+    /// the bass note. The chord tones are rotated so that the bass note comes first and the rest
+    /// keep their order, e.g. C/E gives E G C. A bass note that is not a chord tone is put before
+    /// the chord in root position. All notes are in modulo 12.
+    pub fn notes_voiced(&self) -> Vec<Note> {
+        let mut notes = Self {
+            bass: None,
+            ..self.clone()
+        }
+        .notes();
+        if let Some(bass) = self.bass {
+            match notes.iter().position(|&note| note == bass) {
+                Some(index) => notes.rotate_left(index),
+                None => notes.insert(0, bass),
+            }
+        }
+        notes
+    }
+
+    /// The nth inversion of the chord, i.e. the chord with the nth chord tone of `notes()` in root
+    /// position as its bass note, e.g. C inverted by 1 is C/E. `n` wraps around the number of notes,
+    /// and inversion 0 is the chord in root position.
+    pub fn invert(&self, n: usize) -> Chord {
+        let root_position = Self {
+            bass: None,
+            ..self.clone()
+        };
+        let notes = root_position.notes();
+        let bass = notes[n % notes.len()];
+        Self {
+            bass: Some(bass).filter(|&bass| bass != self.root),
+            ..root_position
+        }
+    }
+
+    /// List all notes of current chord as ascending MIDI note numbers. The first note of `notes()`
+    /// is placed in `octave` (in scientific pitch notation), and each following note is placed
+    /// above the previous one, so that upper extensions stay above the root.
+    ///
+    /// Notes out of the MIDI range are clamped into the range, and notes clamped to the same MIDI
+    /// note number are only listed once.
+    pub fn midi_notes(&self, octave: i32) -> Vec<u8> {
+        clamp_midi(stack_notes(&self.notes(), octave))
+    }
+
+    /// List all notes of current chord as ascending MIDI note numbers like `midi_notes`, with the
+    /// first note placed at `base_note` plus its note number, i.e. `base_note` is the MIDI note
+    /// number of note 0 (A).
+    pub(crate) fn midi_notes_from_base(&self, base_note: u8) -> Vec<u8> {
+        let notes = self.notes();
+        let first = notes
+            .first()
+            .map_or(0, |&note| base_note as i32 + note as i32);
+        clamp_midi(stack_from(&notes, first))
+    }
 }
 
 impl TryFrom<&str> for Chord {
     type Error = error::Error;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let (root_note, key, str_next) = consume_to_note(value)?;
-        let str_next_count = str_next.chars().count();
+        Self::parse_with_convention(value, NoteConvention::English)
+    }
+}
+
+impl FromStr for Chord {
+    type Err = error::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
+    }
+}
+
+/// Options of parsing chords with `Chord::parse_with`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    convention: NoteConvention,
+    default_note_num: [u8; 9], // number of notes of chords without a number, by quality
+}
+
+impl Default for ParseOptions {
+    /// English note names, with triads by default except for dominant and half diminished chords,
+    /// which are seventh chords, as in `try_from`.
+    fn default() -> Self {
+        Self {
+            convention: NoteConvention::English,
+            default_note_num: ChordQuality::ALL.map(default_note_num),
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Convention of note names.
+    pub fn convention(mut self, convention: NoteConvention) -> Self {
+        self.convention = convention;
+        self
+    }
+
+    /// Number of notes of chords with `quality` written without a number, e.g. 4 to parse "C" as
+    /// CM7. Only chords without any number are affected, so "Cadd9" and "C6" are still triads with
+    /// an added note. The number is clamped to the numbers `quality` can be written with, i.e. 3..=7,
+    /// or 2 for power chords.
+    pub fn default_note_num(mut self, quality: ChordQuality, note_num: u8) -> Self {
+        let min = default_note_num(quality).min(3);
+        let max = quality.relative_pitch().len() as u8;
+        self.default_note_num[quality as usize] = note_num.clamp(min, max);
+        self
+    }
+}
+
+impl Chord {
+    /// Parse a chord like `try_from`, with note names in `convention`, e.g. "H7" is B7 in the German
+    /// convention.
+    pub fn parse_with_convention(
+        value: &str,
+        convention: NoteConvention,
+    ) -> Result<Self, error::Error> {
+        Self::parse_with(value, ParseOptions::default().convention(convention))
+    }
+
+    /// Parse a chord like `try_from` with the given options.
+    pub fn parse_with(value: &str, options: ParseOptions) -> Result<Self, error::Error> {
+        let convention = options.convention;
+        // Slash chord. The part after the slash must be a single note.
+        if let Some((chord_str, bass_str)) = value.split_once('/') {
+            let (bass, _, rest) = consume_to_note_with_convention(bass_str, convention)?;
+            if !rest.is_empty() {
+                Err(error::Error::InvalidBassNote(bass_str.to_string()))?
+            }
+            return Ok(Self {
+                bass: Some(bass),
+                ..Self::parse_with(chord_str, options)?
+            });
+        }
+
+        // Altered chord tones after the number of the chord, e.g. "C7b9" or "C7#5b9". The part
+        // before an alteration must end with a number, so that "Cb5" is still a power chord.
+        for (prefix, shift) in [("b", -1), ("♭", -1), ("#", 1), ("♯", 1)] {
+            for degree in [5, 9, 11, 13] {
+                let Some(rest) = value.strip_suffix(&format!("{}{}", prefix, degree)) else {
+                    continue;
+                };
+                if !rest.ends_with(|ch: char| ch.is_ascii_digit()) {
+                    continue;
+                }
+                let mut chord = Self::parse_with(rest, options)?;
+                chord.altered.retain(|&(other, _)| other != degree);
+                chord.altered.push((degree, shift));
+                chord.altered.sort_unstable();
+                return Ok(chord);
+            }
+        }
+
+        // Omitted chord tone at the end, e.g. "C7(no5)" or "C7omit5". Only the third and the fifth
+        // can be omitted, and only if the chord has such a tone, so neither can be omitted from a
+        // power chord.
+        for (prefix, suffix) in [("(no", ")"), ("omit", "")] {
+            if let Some(index) = value.rfind(prefix) {
+                let degree = value[index + prefix.len()..]
+                    .strip_suffix(suffix)
+                    .and_then(|degree_str| degree_str.parse::<u8>().ok())
+                    .filter(|degree| matches!(degree, 3 | 5))
+                    .ok_or_else(|| error::Error::InvalidQuality(value[index..].to_string()))?;
+                let mut chord = Self::parse_with(value[..index].trim_end(), options)?;
+                if chord.quality == ChordQuality::Power || chord.tone_index(degree).is_none() {
+                    Err(error::Error::InvalidQuality(value[index..].to_string()))?
+                }
+                if !chord.omitted.contains(&degree) {
+                    chord.omitted.push(degree);
+                    chord.omitted.sort_unstable();
+                }
+                return Ok(chord);
+            }
+        }
+
+        let (root_note, key, str_next) = consume_to_note_with_convention(value, convention)?;
+        // Lead sheets sometimes put a space between the root and the quality, e.g. "C maj7".
+        let str_next = str_next.trim_start();
+
+        // Suspended chords contain a digit in their quality, so they are handled before splitting.
+        // The number of notes is written before the quality, e.g. "C7sus4".
+        if let Some((size_str, sus_str)) = str_next
+            .split_once("sus")
+            .filter(|(size_str, _)| size_str.chars().all(|ch| ch.is_ascii_digit()))
+        {
+            let quality = match sus_str {
+                "2" => ChordQuality::Sus2,
+                "4" | "" => ChordQuality::Sus4,
+                _ => Err(error::Error::InvalidQuality(format!("sus{}", sus_str)))?,
+            };
+            let note_num = match size_str.parse::<u8>() {
+                Err(_) => 3,
+                Ok(chord_num) if chord_num % 2 == 1 && (3..=13).contains(&chord_num) => {
+                    chord_num.div_ceil(2)
+                }
+                Ok(chord_num) => Err(error::Error::InvalidChordNumber(chord_num))?,
+            };
+            return Ok(Self {
+                root: root_note,
+                note_num,
+                quality,
+                added: Vec::new(),
+                altered: Vec::new(),
+                omitted: Vec::new(),
+                bass: None,
+            });
+        }
+
+        // Byte index, since the quality may contain non-ASCII characters like "ø".
+        let str_next_count = str_next.len();
         let split_index = str_next
-            .chars()
-            .position(|ch| ch.is_digit(10))
+            .find(|ch: char| ch.is_ascii_digit())
             .unwrap_or(str_next_count);
 
+        // An "add" right before the number means the number is an added note rather than the chord size.
+        let (quality_str, is_add) = match str_next[0..split_index].strip_suffix("add") {
+            Some(quality_str) => (quality_str, true),
+            None => (&str_next[0..split_index], false),
+        };
+
         // Determine the quality of chord. Optional because quality may not be specified at this point.
-        let quality_str = &str_next[0..split_index];
         let quality = match quality_str {
             "M" | "maj" => Some(ChordQuality::Maj),
             "m" | "min" => Some(ChordQuality::Min),
             "+" | "aug" => Some(ChordQuality::Aug),
-            "o" | "dim" => Some(ChordQuality::Dim),
+            "o" | "°" | "dim" => Some(ChordQuality::Dim),
             "ø" => Some(ChordQuality::HalfDim),
+            "dom" => Some(ChordQuality::Dom),
             "" => None,
-            _ => Err(format!("Invalid chord quality: {}", quality_str))?,
+            _ => Err(error::Error::InvalidQuality(quality_str.to_string()))?,
         };
+        let triad_quality = quality.unwrap_or(if key {
+            ChordQuality::Maj
+        } else {
+            ChordQuality::Min
+        });
 
         // Determine the number of notes in the chord
-        let (note_num, quality_some) = if split_index == str_next_count {
+        let (note_num, quality_some, added) = if split_index == str_next_count {
+            if is_add {
+                Err(error::Error::InvalidFormat)?
+            }
             // No number indicating notes in the chord. Default number of notes depends on chord quality.
-            let quality_some = quality.unwrap_or(if key {
-                ChordQuality::Maj
-            } else {
-                ChordQuality::Min
-            });
             (
-                match quality_some {
-                    ChordQuality::Maj
-                    | ChordQuality::Min
-                    | ChordQuality::Aug
-                    | ChordQuality::Dim => 3,
-                    ChordQuality::Dom | ChordQuality::HalfDim => 4,
-                },
-                quality_some,
+                options.default_note_num[triad_quality as usize],
+                triad_quality,
+                Vec::new(),
             )
-        } else {
-            if let Ok(chord_num) = str_next[split_index..].parse::<u8>() {
-                if chord_num % 2 == 1 {
-                    (
-                        (chord_num + 1) / 2,
-                        quality.unwrap_or(if key {
-                            ChordQuality::Dom
-                        } else {
-                            ChordQuality::Min
-                        }),
-                    )
-                } else {
-                    Err(format!("Invalid chord number: {}", chord_num))?
+        } else if let Ok(chord_num) = str_next[split_index..].parse::<u8>() {
+            if is_add || chord_num == 6 {
+                // Added notes are put on top of the default chord of the given quality.
+                if added_interval(chord_num).is_none() {
+                    Err(error::Error::InvalidAddedNote(chord_num))?
                 }
+                (
+                    default_note_num(triad_quality),
+                    triad_quality,
+                    vec![chord_num],
+                )
+            } else if chord_num == 5 && quality.is_none() {
+                // A bare 5 is a power chord rather than a triad, e.g. "C5".
+                (2, ChordQuality::Power, Vec::new())
+            } else if chord_num % 2 == 1 && (3..=13).contains(&chord_num) {
+                (
+                    chord_num.div_ceil(2),
+                    quality.unwrap_or(if key {
+                        ChordQuality::Dom
+                    } else {
+                        ChordQuality::Min
+                    }),
+                    Vec::new(),
+                )
             } else {
-                Err("Invalid string format")?
+                Err(error::Error::InvalidChordNumber(chord_num))?
             }
+        } else {
+            Err(error::Error::InvalidFormat)?
         };
         Ok(Self {
             root: root_note,
             note_num,
             quality: quality_some,
+            added,
+            altered: Vec::new(),
+            omitted: Vec::new(),
+            bass: None,
+        })
+    }
+}
+
+/// Transpose every chord in the sequence by `semitones`.
+pub fn transpose_sequence(chords: &[Chord], semitones: i8) -> Vec<Chord> {
+    chords
+        .iter()
+        .map(|chord| chord.transpose(semitones))
+        .collect()
+}
+
+/// Transpose every chord in the sequence from key `from` to key `to`, by the interval between the
+/// tonics of the two keys. The modes of the keys are not considered.
+pub fn transpose_to_key(chords: &[Chord], from: Key, to: Key) -> Vec<Chord> {
+    transpose_sequence(chords, (to.tonic() + 12 - from.tonic()) as i8)
+}
+
+/// Stack the notes ascending as MIDI note numbers, with the first note in `octave`.
+fn stack_notes(notes: &[Note], octave: i32) -> Vec<i32> {
+    let first = notes.first().map_or(0, |&note| midi_number(note, octave));
+    stack_from(notes, first)
+}
+
+/// Stack the notes ascending as MIDI note numbers, with the first note at `first`, and each
+/// following note at the lowest pitch above the previous one.
+fn stack_from(notes: &[Note], first: i32) -> Vec<i32> {
+    let mut ans: Vec<i32> = Vec::new();
+    for &note in notes {
+        let mut midi_note = first + note as i32 - notes[0] as i32;
+        if let Some(&last) = ans.last() {
+            while midi_note <= last {
+                midi_note += 12;
+            }
+        }
+        ans.push(midi_note);
+    }
+    ans
+}
+
+/// Clamp ascending MIDI note numbers into the MIDI range, listing notes clamped to the same MIDI
+/// note number once.
+fn clamp_midi(midi_notes: Vec<i32>) -> Vec<u8> {
+    let mut ans: Vec<u8> = midi_notes
+        .into_iter()
+        .map(|midi_note| midi_note.clamp(0, 127) as u8)
+        .collect();
+    ans.dedup();
+    ans
+}
+
+/// Total movement in semitones between two voicings, i.e. the distance from each note of either
+/// voicing to the nearest note of the other voicing. Voicings of different sizes can be compared.
+fn voicing_distance(a: &[i32], b: &[i32]) -> i32 {
+    let nearest =
+        |note: i32, other: &[i32]| other.iter().map(|&o| (note - o).abs()).min().unwrap_or(0);
+    a.iter().map(|&note| nearest(note, b)).sum::<i32>()
+        + b.iter().map(|&note| nearest(note, a)).sum::<i32>()
+}
+
+/// Voice the chord sequence as MIDI note numbers for smooth playback. The first chord is voiced
+/// like `midi_notes(octave)`, and each following chord takes the inversion and octave, with its
+/// lowest note within one octave of `octave`, that moves the least from the previous chord. The
+/// bass note of a slash chord is always the lowest note.
+///
+/// Notes out of the MIDI range are clamped into the range.
+pub fn voice_lead(chords: &[Chord], octave: i32) -> Vec<Vec<u8>> {
+    let mut ans: Vec<Vec<i32>> = Vec::new();
+    for chord in chords {
+        let notes = chord.notes();
+        let voicing = match ans.last() {
+            None => stack_notes(&notes, octave),
+            Some(last) => {
+                // the bass note of slash chords stays in place, and the other notes are rotated
+                let fixed = usize::from(chord.bass().is_some());
+                (0..(notes.len() - fixed).max(1))
+                    .flat_map(|rotation| {
+                        let mut rotated = notes.clone();
+                        rotated[fixed..].rotate_left(rotation);
+                        (octave - 1..=octave + 1).map(move |o| stack_notes(&rotated, o))
+                    })
+                    .min_by_key(|voicing| voicing_distance(last, voicing))
+                    .unwrap_or_default()
+            }
+        };
+        ans.push(voicing);
+    }
+    ans.into_iter()
+        .map(|voicing| {
+            voicing
+                .into_iter()
+                .map(|midi_note| midi_note.clamp(0, 127) as u8)
+                .collect()
         })
+        .collect()
+}
+
+/// Default number of notes in a chord of given quality when no number is specified.
+fn default_note_num(quality: ChordQuality) -> u8 {
+    match quality {
+        ChordQuality::Maj
+        | ChordQuality::Min
+        | ChordQuality::Aug
+        | ChordQuality::Dim
+        | ChordQuality::Sus2
+        | ChordQuality::Sus4 => 3,
+        ChordQuality::Dom | ChordQuality::HalfDim => 4,
+        ChordQuality::Power => 2,
+    }
+}
+
+/// Interval in semitones above the root of the unaltered chord tone with scale degree `degree`, as
+/// in a dominant chord.
+fn altered_interval(degree: u8) -> Note {
+    ChordQuality::Dom.relative_pitch()[(degree as usize - 1) / 2]
+}
+
+/// Interval in semitones above the root of an added note with scale degree `degree`, or `None` if
+/// the degree cannot be added to a chord.
+fn added_interval(degree: u8) -> Option<Note> {
+    match degree {
+        2 => Some(2),
+        4 => Some(5),
+        6 => Some(9),
+        9 => Some(14),
+        11 => Some(17),
+        13 => Some(21),
+        _ => None,
     }
 }
 
 impl Display for Chord {
+    /// Name of the chord with notes spelled with sharps, e.g. "A#m7/G#". The name is always parsed
+    /// back to the same chord by `try_from`. Use `display_in_key` to spell notes with flats.
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let bass_name = self.bass.map(|bass| note_string(bass)[0]);
         write!(
             f,
             "{}",
-            note_string(self.root)
-                .iter()
-                .map(|note_name| if self.note_num == 3 {
-                    if self.quality == ChordQuality::Maj {
-                        format!("{}", note_name)
-                    } else {
-                        format!("{}{}", note_name, self.quality)
-                    }
-                } else {
-                    if self.quality == ChordQuality::Dom {
-                        format!("{}{}", note_name, self.note_num * 2 - 1)
-                    } else {
-                        format!("{}{}{}", note_name, self.quality, self.note_num * 2 - 1)
-                    }
-                })
-                .fold(String::new(), |a, b| if a == "" { b } else { a + "/" + &b })
+            self.name_with(note_string(self.root)[0], bass_name)
         )
     }
 }
@@ -175,10 +889,23 @@ impl Debug for Chord {
             self.quality
         )?;
         if self.note_num == 3 {
-            write!(f, "triad)")
+            write!(f, "triad")?;
         } else {
-            write!(f, "{})", self.note_num * 2 - 1)
+            write!(f, "{}", self.note_num * 2 - 1)?;
         }
+        for degree in &self.added {
+            write!(f, " add {}", degree)?;
+        }
+        for (degree, shift) in &self.altered {
+            write!(f, " alter {} by {}", degree, shift)?;
+        }
+        for degree in &self.omitted {
+            write!(f, " no {}", degree)?;
+        }
+        if let Some(bass) = self.bass {
+            write!(f, " over {}", bass)?;
+        }
+        write!(f, ")")
     }
 }
 
@@ -187,6 +914,51 @@ impl Hash for Chord {
         state.write_u8(self.root);
         state.write_u8(self.quality as u8);
         state.write_u8(self.note_num);
+        for degree in &self.added {
+            state.write_u8(*degree);
+        }
+        for (degree, shift) in &self.altered {
+            state.write_u8(*degree);
+            state.write_i8(*shift);
+        }
+        for degree in &self.omitted {
+            state.write_u8(*degree);
+        }
+        if let Some(bass) = self.bass {
+            state.write_u8(bass);
+        }
+    }
+}
+
+impl PartialOrd for Chord {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Chord {
+    /// Chords are sorted by root, then number of notes, then quality. Chords that only differ in
+    /// added, altered or omitted notes or bass note are sorted by them last, so that the order is
+    /// consistent with `Eq`.
+    fn cmp(&self, other: &Self) -> Ordering {
+        (
+            self.root,
+            self.note_num,
+            self.quality,
+            &self.added,
+            &self.altered,
+            &self.omitted,
+            self.bass,
+        )
+            .cmp(&(
+                other.root,
+                other.note_num,
+                other.quality,
+                &other.added,
+                &other.altered,
+                &other.omitted,
+                other.bass,
+            ))
     }
 }
 
@@ -197,6 +969,10 @@ impl Default for Chord {
             root: 0,
             note_num: 3,
             quality: ChordQuality::Maj,
+            added: Vec::new(),
+            altered: Vec::new(),
+            omitted: Vec::new(),
+            bass: None,
         }
     }
 }
@@ -204,6 +980,7 @@ impl Default for Chord {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::Error;
 
     #[test]
     fn test_from_string() {
@@ -227,16 +1004,296 @@ mod tests {
         assert_eq!(c5.note_num, 4);
         assert_eq!(c5.root, 0);
         assert_eq!(c5.quality, ChordQuality::Maj);
+        let c6 = Chord::try_from("Dsus4").unwrap();
+        assert_eq!(c6.note_num, 3);
+        assert_eq!(c6.root, 5);
+        assert_eq!(c6.quality, ChordQuality::Sus4);
+        let c7 = Chord::try_from("Dsus").unwrap();
+        assert_eq!(c7, c6);
+        let c8 = Chord::try_from("C/E").unwrap();
+        assert_eq!(c8.note_num, 3);
+        assert_eq!(c8.root, 3);
+        assert_eq!(c8.quality, ChordQuality::Maj);
+        assert_eq!(c8.bass, Some(7));
+        assert_eq!(c1.bass, None);
+    }
+
+    #[test]
+    fn test_accessors() {
+        let g7 = Chord::try_from("G7").unwrap();
+        assert_eq!(g7.root(), 10);
+        assert_eq!(g7.quality(), ChordQuality::Dom);
+        assert_eq!(g7.note_num(), 4);
+        assert_eq!(g7.notes(), [10, 2, 5, 8]);
+        assert!(g7.added().is_empty());
+        assert_eq!(g7.bass(), None);
+        let c_add_9 = Chord::try_from("Cadd9/E").unwrap();
+        assert_eq!(c_add_9.added(), [9]);
+        assert_eq!(c_add_9.bass(), Some(7));
+    }
+
+    #[test]
+    fn test_from_roman() {
+        let c_major = Key::major(3);
+        let v7 = Chord::try_from_roman("V7", c_major).unwrap();
+        assert_eq!(v7, Chord::try_from("G7").unwrap());
+        let vi = Chord::try_from_roman("vi", c_major).unwrap();
+        assert_eq!(vi, Chord::try_from("Am").unwrap());
+        let ii7 = Chord::try_from_roman("ii7", c_major).unwrap();
+        assert_eq!(ii7, Chord::try_from("Dm7").unwrap());
+        let vii_dim = Chord::try_from_roman("vii°", c_major).unwrap();
+        assert_eq!(vii_dim, Chord::try_from("Bdim").unwrap());
+        let flat_vii = Chord::try_from_roman("bVII", c_major).unwrap();
+        assert_eq!(flat_vii, Chord::try_from("Bb").unwrap());
+        let iv_maj7 = Chord::try_from_roman("IVM7", c_major).unwrap();
+        assert_eq!(iv_maj7, Chord::try_from("FM7").unwrap());
+        let vi_add_9 = Chord::try_from_roman("viadd9", c_major).unwrap();
+        assert_eq!(vi_add_9, Chord::try_from("Amadd9").unwrap());
+        let iii = Chord::try_from_roman("III", Key::minor(0)).unwrap();
+        assert_eq!(iii, Chord::try_from("C").unwrap());
+
+        let err1 = Chord::try_from_roman("IIV", c_major);
+        assert_eq!(
+            err1.unwrap_err(),
+            error::Error::InvalidRomanNumeral("IIV".to_string())
+        );
+        let err2 = Chord::try_from_roman("Vi", c_major);
+        assert_eq!(
+            err2.unwrap_err(),
+            error::Error::InvalidRomanNumeral("Vi".to_string())
+        );
+    }
+
+    #[test]
+    fn test_from_roman_case() {
+        let c_major = Key::major(3);
+        let a_minor = Key::minor(0);
+        let roman = |numeral: &str, key: Key| Chord::try_from_roman(numeral, key).unwrap();
+        let chord = |name: &str| Chord::try_from(name).unwrap();
+        assert_eq!(roman("vi", c_major), chord("Am"));
+        assert_eq!(roman("VI", c_major), chord("A"));
+        assert_eq!(roman("ii°", c_major), chord("Ddim"));
+        assert_eq!(roman("viiø7", c_major), chord("Bø7"));
+        assert_eq!(roman("i", a_minor), chord("Am"));
+        assert_eq!(roman("I", a_minor), chord("A"));
+        assert_eq!(roman("ii°", a_minor), chord("Bdim"));
+    }
+
+    #[test]
+    fn test_function() {
+        let c_major = Key::major(3);
+        let function = |name: &str, key: Key| Chord::try_from(name).unwrap().function(key);
+        assert_eq!(function("G7", c_major), Some(HarmonicFunction::Dominant));
+        assert_eq!(function("Dm", c_major), Some(HarmonicFunction::Subdominant));
+        assert_eq!(function("Am7", c_major), Some(HarmonicFunction::Tonic));
+        assert_eq!(function("Bø7", c_major), Some(HarmonicFunction::Dominant));
+        assert_eq!(function("CM7/E", c_major), Some(HarmonicFunction::Tonic));
+        // chromatic chords
+        assert_eq!(function("D7", c_major), None);
+        assert_eq!(function("Bb", c_major), None);
+        let a_minor = Key::minor(0);
+        assert_eq!(function("E7", a_minor), Some(HarmonicFunction::Dominant));
+        assert_eq!(function("G#dim", a_minor), Some(HarmonicFunction::Dominant));
+        assert_eq!(function("Dm", a_minor), Some(HarmonicFunction::Subdominant));
+        assert_eq!(function("C", a_minor), Some(HarmonicFunction::Tonic));
+    }
+
+    #[test]
+    fn test_display_in_key() {
+        let b_flat = Chord::try_from("Bb").unwrap();
+        assert_eq!(format!("{}", b_flat), "A#");
+        assert_eq!(b_flat.display_in_key(Key::major(8)), "Bb");
+        assert_eq!(b_flat.display_in_key(Key::major(10)), "A#");
+        let f_sharp_min = Chord::try_from("F#m7/C#").unwrap();
+        assert_eq!(f_sharp_min.display_in_key(Key::major(0)), "F#m7/C#");
+        assert_eq!(f_sharp_min.display_in_key(Key::minor(8)), "Gbm7/Db");
+        let c = Chord::try_from("C").unwrap();
+        assert_eq!(c.display_in_key(Key::major(1)), "C");
+    }
+
+    #[test]
+    fn test_from_string_flat() {
+        let c1 = Chord::try_from("Ab").unwrap();
+        assert_eq!(c1.root, 11);
+        assert_eq!(c1.quality, ChordQuality::Maj);
+        assert_eq!(format!("{}", c1), "G#");
+        let c2 = Chord::try_from("Abm7").unwrap();
+        assert_eq!(c2.root, 11);
+        assert_eq!(c2.note_num, 4);
+        assert_eq!(c2.quality, ChordQuality::Min);
+        assert_eq!(format!("{}", c2), "G#m7");
+        let c3 = Chord::try_from("Cb").unwrap();
+        assert_eq!(c3.root, 2);
+        assert_eq!(format!("{}", c3), "B");
+        let c4 = Chord::try_from("Fb").unwrap();
+        assert_eq!(c4.root, 7);
+    }
+
+    #[test]
+    fn test_parse_with() {
+        let sevenths = ParseOptions::default()
+            .default_note_num(ChordQuality::Maj, 4)
+            .default_note_num(ChordQuality::Min, 4);
+        let c = Chord::parse_with("C", sevenths).unwrap();
+        assert_eq!(c.note_num(), 4);
+        assert_eq!(c, Chord::try_from("CM7").unwrap());
+        assert_eq!(
+            Chord::parse_with("Am/G", sevenths).unwrap(),
+            Chord::try_from("Am7/G").unwrap()
+        );
+        // explicit numbers and added notes are unchanged
+        assert_eq!(
+            Chord::parse_with("C9", sevenths).unwrap(),
+            Chord::try_from("C9").unwrap()
+        );
+        assert_eq!(
+            Chord::parse_with("Cadd9", sevenths).unwrap(),
+            Chord::try_from("Cadd9").unwrap()
+        );
+        assert_eq!(
+            Chord::parse_with("Hm", sevenths.convention(NoteConvention::German)).unwrap(),
+            Chord::try_from("Bm7").unwrap()
+        );
+        assert_eq!(
+            Chord::parse_with("C", ParseOptions::default()).unwrap(),
+            Chord::try_from("C").unwrap()
+        );
+        // numbers are clamped to chords that can be written
+        let clamped = ParseOptions::default()
+            .default_note_num(ChordQuality::Maj, 1)
+            .default_note_num(ChordQuality::Min, 9)
+            .default_note_num(ChordQuality::Power, 4);
+        let c = Chord::parse_with("C", clamped).unwrap();
+        assert_eq!(c, Chord::try_from("C").unwrap());
+        assert_eq!(Chord::try_from(c.to_string().as_str()).unwrap(), c);
+        let a_min = Chord::parse_with("Am", clamped).unwrap();
+        assert_eq!(a_min, Chord::try_from("Am13").unwrap());
+        assert_eq!(Chord::try_from(a_min.to_string().as_str()).unwrap(), a_min);
+        assert_eq!(
+            Chord::parse_with("C5", clamped).unwrap(),
+            Chord::try_from("C5").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_with_convention() {
+        let input = "Bm7/H";
+        assert_eq!(
+            Chord::parse_with_convention(input, NoteConvention::English),
+            Err(Error::InvalidNote('H'))
+        );
+        let chord = Chord::parse_with_convention(input, NoteConvention::German).unwrap();
+        assert_eq!(chord.root(), 1);
+        assert_eq!(chord.bass(), Some(2));
+        assert_eq!(chord, Chord::try_from("Bbm7/B").unwrap());
+        assert_eq!(
+            Chord::parse_with_convention("C", NoteConvention::German).unwrap(),
+            Chord::try_from("C").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_chord_not_pitch() {
+        // the digit after the root is the chord size, not the octave
+        let c_7 = Chord::try_from("C7").unwrap();
+        assert_eq!(c_7.quality(), ChordQuality::Dom);
+        assert_eq!(c_7.note_num(), 4);
+        assert_eq!(crate::note::consume_to_pitch("C7"), Ok((96, "")));
+        assert_eq!(crate::note::consume_to_pitch("C4"), Ok((60, "")));
+        assert_eq!(
+            Chord::try_from("C4").unwrap_err(),
+            Error::InvalidChordNumber(4)
+        );
+    }
+
+    #[test]
+    fn test_from_string_double_accidental() {
+        assert_eq!(
+            Chord::try_from("Cx").unwrap(),
+            Chord::try_from("D").unwrap()
+        );
+        assert_eq!(
+            Chord::try_from("Dbbm7").unwrap(),
+            Chord::try_from("Cm7").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_from_string_whitespace() {
+        assert_eq!(
+            Chord::try_from("C maj7").unwrap(),
+            Chord::try_from("CM7").unwrap()
+        );
+        assert_eq!(
+            Chord::try_from("A m7").unwrap(),
+            Chord::try_from("Am7").unwrap()
+        );
+        assert_eq!(
+            Chord::try_from("A  min").unwrap(),
+            Chord::try_from("Am").unwrap()
+        );
+        assert_eq!(
+            Chord::try_from("F# m7/C#").unwrap(),
+            Chord::try_from("F#m7/C#").unwrap()
+        );
+        // the file parser still splits chords on whitespace
+        assert_eq!(
+            crate::progression::parse_progression("C Am").unwrap(),
+            [
+                Chord::try_from("C").unwrap(),
+                Chord::try_from("Am").unwrap()
+            ]
+        );
     }
 
     #[test]
     fn test_from_string_err() {
         let c1 = Chord::try_from("H");
-        assert_eq!(c1.unwrap_err(), "Invalid note character: H");
-        let c2 = Chord::try_from("Csus2");
-        assert_eq!(c2.unwrap_err(), "Invalid chord quality: sus");
-        let c3 = Chord::try_from("C#6");
-        assert_eq!(c3.unwrap_err(), "Invalid chord number: 6");
+        assert_eq!(c1.unwrap_err(), Error::InvalidNote('H'));
+        let c2 = Chord::try_from("Csus3");
+        assert_eq!(c2.unwrap_err(), Error::InvalidQuality("sus3".to_string()));
+        let c3 = Chord::try_from("C#8");
+        assert_eq!(c3.unwrap_err(), Error::InvalidChordNumber(8));
+        let c8 = Chord::try_from("C15");
+        assert_eq!(c8.unwrap_err(), Error::InvalidChordNumber(15));
+        let c9 = Chord::try_from("C1");
+        assert_eq!(c9.unwrap_err(), Error::InvalidChordNumber(1));
+        let c4 = Chord::try_from("Cadd7");
+        assert_eq!(c4.unwrap_err(), Error::InvalidAddedNote(7));
+        let c5 = Chord::try_from("C/Em");
+        assert_eq!(c5.unwrap_err(), Error::InvalidBassNote("Em".to_string()));
+        let c6 = Chord::try_from("C/");
+        assert_eq!(c6.unwrap_err(), Error::InvalidNoteFormat);
+        let c7 = Chord::try_from("/E");
+        assert_eq!(c7.unwrap_err(), Error::InvalidNoteFormat);
+    }
+
+    #[test]
+    fn test_parse() {
+        fn parse_all(names: &[&str]) -> Result<Vec<Chord>, Error> {
+            let mut chords = Vec::new();
+            for name in names {
+                chords.push(name.parse::<Chord>()?);
+            }
+            Ok(chords)
+        }
+        let cm7: Chord = "Cm7".parse().unwrap();
+        assert_eq!(cm7, Chord::try_from("Cm7").unwrap());
+        assert_eq!(parse_all(&["C", "G/B", "Am7"]).unwrap().len(), 3);
+        assert_eq!(
+            parse_all(&["C", "Csus3", "H"]),
+            Err(Error::InvalidQuality("sus3".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_error_message() {
+        let c1 = Chord::try_from("H");
+        assert_eq!(c1.unwrap_err().to_string(), "Invalid note character: H");
+        let c2 = Chord::try_from("Csus3");
+        assert_eq!(c2.unwrap_err().to_string(), "Invalid chord quality: sus3");
+        let c3 = Chord::try_from("C#8");
+        assert_eq!(c3.unwrap_err().to_string(), "Invalid chord number: 8");
     }
 
     #[test]
@@ -253,6 +1310,414 @@ mod tests {
         assert_eq!(c_dom_7.notes(), [3, 7, 10, 1]);
         let c_aug = Chord::try_from("Caug").unwrap();
         assert_eq!(c_aug.notes(), [3, 7, 11]);
+        let c_9 = Chord::try_from("C9").unwrap();
+        assert_eq!(c_9.note_num, 5);
+        assert_eq!(c_9.notes(), [3, 7, 10, 1, 5]);
+        let c_11 = Chord::try_from("C11").unwrap();
+        assert_eq!(c_11.note_num, 6);
+        assert_eq!(c_11.notes(), [3, 7, 10, 1, 5, 8]);
+        let c_13 = Chord::try_from("C13").unwrap();
+        assert_eq!(c_13.note_num, 7);
+        assert_eq!(c_13.notes(), [3, 7, 10, 1, 5, 8, 0]);
+        let c_min_11 = Chord::try_from("Cm11").unwrap();
+        assert_eq!(c_min_11.notes(), [3, 6, 10, 1, 5, 8]);
+        let c_sus2 = Chord::try_from("Csus2").unwrap();
+        assert_eq!(c_sus2.notes(), [3, 5, 10]);
+        let c_sus4 = Chord::try_from("Csus4").unwrap();
+        assert_eq!(c_sus4.notes(), [3, 8, 10]);
+        let c_6 = Chord::try_from("C6").unwrap();
+        assert_eq!(c_6.notes(), [3, 7, 10, 0]);
+        let c_min_6 = Chord::try_from("Cm6").unwrap();
+        assert_eq!(c_min_6.notes(), [3, 6, 10, 0]);
+        let c_add_9 = Chord::try_from("Cadd9").unwrap();
+        assert_eq!(c_add_9.notes(), [3, 7, 10, 5]);
+        let c_over_e = Chord::try_from("C/E").unwrap();
+        assert_eq!(c_over_e.notes(), [7, 3, 10]);
+        let c_over_d = Chord::try_from("C/D").unwrap();
+        assert_eq!(c_over_d.notes(), [5, 3, 7, 10]);
+    }
+
+    #[test]
+    fn test_diminished() {
+        let c_dim = Chord::try_from("Cdim").unwrap();
+        assert_eq!(c_dim.note_num(), 3);
+        assert_eq!(c_dim.notes(), [3, 6, 9]);
+        let c_dim_7 = Chord::try_from("Cdim7").unwrap();
+        assert_eq!(c_dim_7.quality(), ChordQuality::Dim);
+        assert_eq!(c_dim_7.note_num(), 4);
+        assert_eq!(c_dim_7.notes(), [3, 6, 9, 0]);
+        assert_eq!(Chord::try_from("Co").unwrap(), c_dim);
+        assert_eq!(Chord::try_from("C°").unwrap(), c_dim);
+        assert_eq!(Chord::try_from("Co7").unwrap(), c_dim_7);
+        assert_eq!(Chord::try_from("C°7").unwrap(), c_dim_7);
+        let c_half_dim_7 = Chord::try_from("Cø7").unwrap();
+        assert_eq!(c_half_dim_7.quality(), ChordQuality::HalfDim);
+        assert_eq!(c_half_dim_7.notes(), [3, 6, 9, 1]);
+        assert_ne!(c_half_dim_7, c_dim_7);
+        assert!(!c_half_dim_7.is_enharmonic(&c_dim_7));
+    }
+
+    #[test]
+    fn test_altered() {
+        let c7_b9 = Chord::try_from("C7b9").unwrap();
+        assert_eq!(c7_b9.altered(), [(9, -1)]);
+        // C E G Bb Db
+        assert_eq!(c7_b9.notes(), [3, 7, 10, 1, 4]);
+        assert_eq!(c7_b9.to_string(), "C7b9");
+        let g7_sharp_5 = Chord::try_from("G7#5").unwrap();
+        // G B D# F
+        assert_eq!(g7_sharp_5.notes(), [10, 2, 6, 8]);
+        assert_eq!(g7_sharp_5.to_string(), "G7#5");
+        let c9_sharp_11 = Chord::try_from("C9♯11").unwrap();
+        assert_eq!(c9_sharp_11.notes(), [3, 7, 10, 1, 5, 9]);
+        let c7_sharp_9_b13 = Chord::try_from("C7b13#9").unwrap();
+        assert_eq!(c7_sharp_9_b13.altered(), [(9, 1), (13, -1)]);
+        assert_eq!(c7_sharp_9_b13.to_string(), "C7#9b13");
+        assert_eq!(c7_sharp_9_b13.notes(), [3, 7, 10, 1, 6, 11]);
+        // the altered ninth replaces the ninth of a ninth chord
+        assert_eq!(Chord::try_from("C9b9").unwrap().notes(), c7_b9.notes());
+        assert!(Chord::try_from("Cm7b5")
+            .unwrap()
+            .is_enharmonic(&Chord::try_from("Cø7").unwrap()));
+        // a flat or sharp right after the root is an accidental
+        assert_eq!(
+            Chord::try_from("Cb5").unwrap().quality(),
+            ChordQuality::Power
+        );
+        assert_eq!(Chord::try_from("C#5").unwrap().root(), 4);
+        for name in ["C7b9", "G7#5", "C7#9b13", "Cm7b5/Gb", "F13#11(no5)"] {
+            let chord = Chord::try_from(name).unwrap();
+            assert_eq!(Chord::try_from(chord.to_string().as_str()), Ok(chord));
+        }
+    }
+
+    #[test]
+    fn test_power_altered() {
+        let c5_b5 = Chord::try_from("C5b5").unwrap();
+        assert_eq!(c5_b5.altered(), [(5, -1)]);
+        assert_eq!(c5_b5.notes(), [3, 9]);
+        assert_eq!(Chord::try_from("C5#5").unwrap().notes(), [3, 11]);
+        assert_eq!(c5_b5.to_string(), "C5b5");
+        // tensions are still put above a power chord
+        assert_eq!(Chord::try_from("C5b9").unwrap().notes(), [3, 10, 4]);
+        // the fifth of a suspended chord is its third tone
+        assert_eq!(Chord::try_from("C7sus4b5").unwrap().notes(), [3, 8, 9, 1]);
+    }
+
+    #[test]
+    fn test_omitted() {
+        let c7_no5 = Chord::try_from("C7(no5)").unwrap();
+        assert_eq!(c7_no5.omitted(), [5]);
+        assert_eq!(c7_no5.notes(), [3, 7, 1]);
+        // G, 7 semitones above C, is dropped
+        assert!(!c7_no5.notes().contains(&10));
+        assert_eq!(c7_no5.to_string(), "C7(no5)");
+        assert_eq!(Chord::try_from("C7omit5"), Ok(c7_no5.clone()));
+        assert_eq!(Chord::try_from("C7 (no5)"), Ok(c7_no5));
+        let c_no3 = Chord::try_from("Cadd9(no3)/G").unwrap();
+        assert_eq!(c_no3.notes(), [10, 3, 5]);
+        assert_eq!(c_no3.to_string(), "Cadd9(no3)/G");
+        assert_eq!(c_no3.intervals(), [7, 7]);
+        let both = Chord::try_from("Cm7(no5)(no3)").unwrap();
+        assert_eq!(both.omitted(), [3, 5]);
+        assert_eq!(both.to_string(), "Cm7(no3)(no5)");
+        assert_eq!(
+            Chord::try_from("C7(no7)").unwrap_err(),
+            Error::InvalidQuality("(no7)".to_string())
+        );
+        assert_eq!(
+            Chord::try_from("C7(no5").unwrap_err(),
+            Error::InvalidQuality("(no5".to_string())
+        );
+        // the fourth of a suspended chord is not its third
+        assert_eq!(Chord::try_from("Csus4(no5)").unwrap().notes(), [3, 8]);
+        assert_eq!(
+            Chord::try_from("Csus4(no3)").unwrap_err(),
+            Error::InvalidQuality("(no3)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_power_omitted() {
+        assert_eq!(
+            Chord::try_from("C5(no3)").unwrap_err(),
+            Error::InvalidQuality("(no3)".to_string())
+        );
+        assert_eq!(
+            Chord::try_from("C5(no5)").unwrap_err(),
+            Error::InvalidQuality("(no5)".to_string())
+        );
+        assert_eq!(
+            Chord::try_from("C5omit5").unwrap_err(),
+            Error::InvalidQuality("omit5".to_string())
+        );
+    }
+
+    #[test]
+    fn test_power_chord() {
+        let c5 = Chord::try_from("C5").unwrap();
+        assert_eq!(c5.quality(), ChordQuality::Power);
+        assert_eq!(c5.note_num(), 2);
+        assert_eq!(c5.notes(), [3, 10]);
+        assert_eq!(c5.to_string(), "C5");
+        assert_eq!(c5.intervals(), [7]);
+        let g5_b = Chord::try_from("G5/B").unwrap();
+        assert_eq!(g5_b.notes(), [2, 10, 5]);
+        assert_eq!(g5_b.to_string(), "G5/B");
+        // a 5 after a quality is still the size of the chord
+        assert_eq!(
+            Chord::try_from("Cm5").unwrap(),
+            Chord::try_from("Cm").unwrap()
+        );
+        assert_eq!(Chord::from_notes(&[3, 10]), Some(c5));
+    }
+
+    #[test]
+    fn test_intervals() {
+        let intervals = |name: &str| Chord::try_from(name).unwrap().intervals();
+        assert_eq!(intervals("C"), [4, 3]);
+        assert_eq!(intervals("Cm"), [3, 4]);
+        assert_eq!(intervals("C7"), [4, 3, 3]);
+        assert_eq!(intervals("CM9"), [4, 3, 4, 3]);
+        assert_eq!(intervals("Cadd9/E"), [4, 3, 7]);
+        assert_eq!(intervals("C6"), [4, 3, 2]);
+    }
+
+    #[test]
+    fn test_distance() {
+        let [c, am, f_sharp, c_sharp, c_e] =
+            ["C", "Am", "F#", "C#", "C/E"].map(|name| Chord::try_from(name).unwrap());
+        assert_eq!(c.distance(&c), 0);
+        assert_eq!(c.distance(&c_e), 0);
+        assert_eq!(c.distance(&am), 5);
+        assert_eq!(am.distance(&c), 5);
+        assert_eq!(c.distance(&f_sharp), 12);
+        // a semitone apart shares no notes, and the roots are far apart on the circle of fifths
+        assert!(c.distance(&c_sharp) > c.distance(&am));
+    }
+
+    #[test]
+    fn test_invert() {
+        let c = Chord::try_from("C").unwrap();
+        let first = c.invert(1);
+        assert_eq!(first, Chord::try_from("C/E").unwrap());
+        assert_eq!(first.notes_voiced(), [7, 10, 3]);
+        assert_eq!(c.invert(2).notes_voiced(), [10, 3, 7]);
+        assert_eq!(c.invert(3), c);
+        assert_eq!(first.invert(0), c);
+        let g7 = Chord::try_from("G7").unwrap();
+        assert_eq!(g7.invert(3), Chord::try_from("G7/F").unwrap());
+        assert_eq!(g7.invert(5), Chord::try_from("G7/B").unwrap());
+    }
+
+    #[test]
+    fn test_midi_notes() {
+        let c_maj_7 = Chord::try_from("CM7").unwrap();
+        assert_eq!(c_maj_7.midi_notes(4), [60, 64, 67, 71]);
+        let a_min = Chord::try_from("Am").unwrap();
+        assert_eq!(a_min.midi_notes(3), [57, 60, 64]);
+        let g_9 = Chord::try_from("G9").unwrap();
+        assert_eq!(g_9.midi_notes(3), [55, 59, 62, 65, 69]);
+        let c_over_e = Chord::try_from("C/E").unwrap();
+        assert_eq!(c_over_e.midi_notes(3), [52, 60, 67]);
+    }
+
+    #[test]
+    fn test_voice_lead() {
+        let chords: Vec<Chord> = ["C", "F", "G7", "C"]
+            .into_iter()
+            .map(|s| Chord::try_from(s).unwrap())
+            .collect();
+        let voicings = voice_lead(&chords, 4);
+        assert_eq!(voicings[0], [60, 64, 67]);
+        // F keeps the common tone C instead of jumping to F A C
+        assert_eq!(voicings[1], [60, 65, 69]);
+        assert_eq!(voicings[2], [59, 62, 65, 67]);
+        assert_eq!(voicings[3], [60, 64, 67]);
+        for (voicing, chord) in voicings.iter().zip(&chords) {
+            let mut notes: Vec<Note> = voicing.iter().map(|&n| ((n + 3) % 12) as Note).collect();
+            let mut expected = chord.notes();
+            notes.sort_unstable();
+            expected.sort_unstable();
+            assert_eq!(notes, expected);
+        }
+
+        // the bass note stays the lowest
+        let chords = [
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("G/B").unwrap(),
+        ];
+        let voicings = voice_lead(&chords, 4);
+        assert_eq!(voicings[1][0], 59);
+        assert_eq!(voice_lead(&[], 4), Vec::<Vec<u8>>::new());
+    }
+
+    #[test]
+    fn test_notes_voiced() {
+        let c = Chord::try_from("C").unwrap();
+        assert_eq!(c.notes_voiced(), c.notes());
+        let c_over_g = Chord::try_from("C/G").unwrap();
+        assert_eq!(c_over_g.notes_voiced(), [10, 3, 7]);
+        let c_over_e = Chord::try_from("C/E").unwrap();
+        assert_eq!(c_over_e.notes_voiced(), [7, 10, 3]);
+        let c_over_d = Chord::try_from("C/D").unwrap();
+        assert_eq!(c_over_d.notes_voiced(), [5, 3, 7, 10]);
+        let g_7_over_f = Chord::try_from("G7/F").unwrap();
+        assert_eq!(g_7_over_f.notes_voiced(), [8, 10, 2, 5]);
+    }
+
+    #[test]
+    fn test_transpose() {
+        let c = Chord::try_from("C").unwrap();
+        assert_eq!(c.transpose(7), Chord::try_from("G").unwrap());
+        assert_eq!(c.transpose(-5), Chord::try_from("G").unwrap());
+        let d = Chord::try_from("D").unwrap();
+        assert_eq!(d.transpose(-2), c);
+        let a_min_7_over_g = Chord::try_from("Am7/G").unwrap();
+        assert_eq!(
+            a_min_7_over_g.transpose(14),
+            Chord::try_from("Bm7/A").unwrap()
+        );
+        assert_eq!(a_min_7_over_g.transpose(-12), a_min_7_over_g);
+        assert_eq!(
+            a_min_7_over_g.transpose(-1),
+            Chord::try_from("G#m7/F#").unwrap()
+        );
+        assert_eq!(
+            a_min_7_over_g.transpose(i8::MIN),
+            a_min_7_over_g.transpose(4)
+        );
+    }
+
+    #[test]
+    fn test_from_notes() {
+        assert_eq!(
+            Chord::from_notes(&[3, 7, 10, 2]),
+            Some(Chord::try_from("CM7").unwrap())
+        );
+        assert_eq!(
+            Chord::from_notes(&[3, 6, 10]),
+            Some(Chord::try_from("Cm").unwrap())
+        );
+        assert_eq!(
+            Chord::from_notes(&[10, 2, 5, 8]),
+            Some(Chord::try_from("G7").unwrap())
+        );
+        assert_eq!(
+            Chord::from_notes(&[7, 10, 3, 15]),
+            Some(Chord::try_from("C/E").unwrap())
+        );
+        assert_eq!(
+            Chord::from_notes(&[2, 5, 8, 11]),
+            Some(Chord::try_from("Bdim7").unwrap())
+        );
+        assert_eq!(Chord::from_notes(&[3, 4, 5]), None);
+        assert_eq!(Chord::from_notes(&[]), None);
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for root in 0..12 {
+            for quality in ChordQuality::ALL {
+                for note_num in 2..=quality.relative_pitch().len() as u8 {
+                    let chord = Chord {
+                        root,
+                        note_num,
+                        quality,
+                        added: Vec::new(),
+                        altered: Vec::new(),
+                        omitted: Vec::new(),
+                        bass: None,
+                    };
+                    assert_eq!(Chord::try_from(chord.to_string().as_str()), Ok(chord));
+                }
+            }
+        }
+        for name in [
+            "Cdom5", "C7sus4", "C9sus2", "Cø5", "Cø7", "C6", "Cm6", "Cadd9", "Cmadd11", "C/E",
+            "Am7/G", "A#m7/G#", "C6/A",
+        ] {
+            let chord = Chord::try_from(name).unwrap();
+            assert_eq!(chord.to_string(), name);
+            assert_eq!(Chord::try_from(chord.to_string().as_str()), Ok(chord));
+        }
+        assert_eq!(
+            Chord::try_from("Cdom").unwrap(),
+            Chord::try_from("C7").unwrap()
+        );
+        assert_eq!(Chord::try_from("C7sus").unwrap().notes(), [3, 8, 10, 1]);
+    }
+
+    #[test]
+    fn test_half_dim_round_trip() {
+        let c_half_dim_7 = Chord::try_from("Cø7").unwrap();
+        assert_eq!(Chord::try_from("Cø").unwrap(), c_half_dim_7);
+        assert_eq!(c_half_dim_7.note_num(), 4);
+        assert_eq!(c_half_dim_7.to_string(), "Cø7");
+        for name in [
+            "Cø",
+            "Cø5",
+            "Cø7",
+            "Cø9",
+            "Cø13",
+            "Cø7/E",
+            "Cø7b9",
+            "Cø7(no5)",
+        ] {
+            let chord = Chord::try_from(name).unwrap();
+            assert_eq!(
+                Chord::try_from(chord.to_string().as_str()),
+                Ok(chord),
+                "{name}"
+            );
+        }
+        // added notes are only parsed on top of a chord without a number
+        for name in ["Cøadd11", "Cøadd6", "Cdomadd9"] {
+            let chord = Chord::try_from(name).unwrap();
+            assert_eq!(chord.to_string(), name);
+            assert_eq!(Chord::try_from(chord.to_string().as_str()), Ok(chord));
+        }
+        assert_eq!(Chord::try_from("Cø6").unwrap().to_string(), "Cøadd6");
+        assert_eq!(Chord::try_from("Cmadd9").unwrap().to_string(), "Cmadd9");
+    }
+
+    #[test]
+    fn test_ord() {
+        let sorted: Vec<Chord> = [
+            "A", "Am", "AM7", "Am7", "A7", "A7/G", "A#", "C", "C/E", "Cadd9", "Em",
+        ]
+        .into_iter()
+        .map(|s| Chord::try_from(s).unwrap())
+        .collect();
+        let mut shuffled = sorted.clone();
+        shuffled.reverse();
+        shuffled.swap(0, 4);
+        shuffled.swap(2, 9);
+        shuffled.sort();
+        assert_eq!(shuffled, sorted);
+        assert_eq!(
+            Chord::try_from("C#")
+                .unwrap()
+                .cmp(&Chord::try_from("Db").unwrap()),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn test_transpose_sequence() {
+        let in_c = [
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("F").unwrap(),
+            Chord::try_from("G7").unwrap(),
+        ];
+        let in_d = [
+            Chord::try_from("D").unwrap(),
+            Chord::try_from("G").unwrap(),
+            Chord::try_from("A7").unwrap(),
+        ];
+        assert_eq!(transpose_sequence(&in_c, 2), in_d);
+        assert_eq!(transpose_to_key(&in_c, Key::major(3), Key::major(5)), in_d);
+        assert_eq!(transpose_to_key(&in_d, Key::major(5), Key::major(3)), in_c);
     }
 
     #[test]
@@ -267,5 +1732,76 @@ mod tests {
         assert_eq!(format!("{}", c_min_7), "Cm7");
         let c_dom_7 = Chord::try_from("C7").unwrap();
         assert_eq!(format!("{}", c_dom_7), "C7");
+        let c_13 = Chord::try_from("C13").unwrap();
+        assert_eq!(format!("{}", c_13), "C13");
+        let c_maj_9 = Chord::try_from("CM9").unwrap();
+        assert_eq!(format!("{}", c_maj_9), "CM9");
+        let c_sus2 = Chord::try_from("Csus2").unwrap();
+        assert_eq!(format!("{}", c_sus2), "Csus2");
+        let c_sus4 = Chord::try_from("Csus").unwrap();
+        assert_eq!(format!("{}", c_sus4), "Csus4");
+        let c_6 = Chord::try_from("C6").unwrap();
+        assert_eq!(format!("{}", c_6), "C6");
+        let c_min_6 = Chord::try_from("Cm6").unwrap();
+        assert_eq!(format!("{}", c_min_6), "Cm6");
+        let c_add_9 = Chord::try_from("Cadd9").unwrap();
+        assert_eq!(format!("{}", c_add_9), "Cadd9");
+        let c_over_e = Chord::try_from("C/E").unwrap();
+        assert_eq!(format!("{}", c_over_e), "C/E");
+        let a_min_over_g = Chord::try_from("Am7/G").unwrap();
+        assert_eq!(format!("{}", a_min_over_g), "Am7/G");
+    }
+
+    #[test]
+    fn test_parse_solfege() {
+        let solfege = ParseOptions::default().convention(NoteConvention::Solfege);
+        for (solfege_name, name) in [
+            ("Do", "C"),
+            ("Lam7", "Am7"),
+            ("Sol7/Si", "G7/B"),
+            ("Mib", "Eb"),
+        ] {
+            assert_eq!(
+                Chord::parse_with(solfege_name, solfege).unwrap(),
+                Chord::try_from(name).unwrap()
+            );
+        }
+        // letter names are still the default, where "Do" is D diminished
+        assert_eq!(
+            Chord::try_from("Do").unwrap(),
+            Chord::try_from("Ddim").unwrap()
+        );
+        assert!(Chord::try_from("Sol").is_err());
+    }
+
+    #[test]
+    fn test_display_with() {
+        let chord = Chord::try_from("A#m7/G#").unwrap();
+        assert_eq!(chord.display_with(AccidentalStyle::Ascii), "A#m7/G#");
+        assert_eq!(chord.display_with(AccidentalStyle::Unicode), "A♯m7/G♯");
+        assert_eq!(Chord::try_from("B♭m7/A♭").unwrap(), chord);
+        for name in ["C♭", "E♯", "F♯7", "G♭M7/B♭", "C7♭9"] {
+            let chord = Chord::try_from(name).unwrap();
+            let unicode = chord.display_with(AccidentalStyle::Unicode);
+            assert_eq!(Chord::try_from(unicode.as_str()).unwrap(), chord, "{name}");
+        }
+        assert_eq!(Chord::try_from("C♭").unwrap().to_string(), "B");
+        assert_eq!(Chord::try_from("E♯").unwrap().to_string(), "F");
+        assert!(Chord::try_from("♯m").is_err());
+        assert!(Chord::try_from("C/♭").is_err());
+    }
+
+    #[test]
+    fn test_is_enharmonic() {
+        let c_sharp = Chord::try_from("C#").unwrap();
+        let d_flat = Chord::try_from("Db").unwrap();
+        assert_eq!(c_sharp, d_flat);
+        assert!(c_sharp.is_enharmonic(&d_flat));
+        let c_6 = Chord::try_from("C6").unwrap();
+        let a_min_7_over_c = Chord::try_from("Am7/C").unwrap();
+        assert_ne!(c_6, a_min_7_over_c);
+        assert!(c_6.is_enharmonic(&a_min_7_over_c));
+        assert!(!c_6.is_enharmonic(&Chord::try_from("C").unwrap()));
+        assert!(!c_sharp.is_enharmonic(&Chord::try_from("C#m").unwrap()));
     }
 }
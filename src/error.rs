@@ -1,4 +1,81 @@
-use std::result;
+use std::{fmt::Display, result};
+
+use crate::{chord::Chord, progression::TokenError};
+
+/// Errors returned when parsing chords or generating chord progressions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    InvalidNote(char),                   // character that is not a note name
+    InvalidNoteFormat,                   // missing note
+    InvalidQuality(String),              // unknown chord quality
+    InvalidChordNumber(u8),              // number that does not denote a chord size
+    InvalidAddedNote(u8),                // scale degree that cannot be added to a chord
+    InvalidBassNote(String),             // bass note of a slash chord that is not a single note
+    InvalidRomanNumeral(String),         // roman numeral that is not a scale degree
+    InvalidFormat,                       // chord string in unexpected format
+    ChordNotInCorpus(Chord),             // chord not appeared in training set
+    EmptyCorpus,                         // no chords are stored in the generator
+    InvalidIndex(usize, usize),          // right index not greater than the index being generated
+    InvalidRange(usize),                 // right index of a range too small to hold both end chords
+    InvalidLengthBounds(usize, usize),   // minimum length greater than the maximum length
+    InvalidProgression(Vec<TokenError>), // chords that cannot be parsed in a progression
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidNote(ch) => write!(f, "Invalid note character: {}", ch),
+            Self::InvalidNoteFormat => write!(f, "Invalid note format"),
+            Self::InvalidQuality(quality) => write!(f, "Invalid chord quality: {}", quality),
+            Self::InvalidChordNumber(num) => write!(f, "Invalid chord number: {}", num),
+            Self::InvalidAddedNote(degree) => write!(f, "Invalid added note: {}", degree),
+            Self::InvalidBassNote(bass) => write!(f, "Invalid bass note: {}", bass),
+            Self::InvalidRomanNumeral(numeral) => write!(f, "Invalid roman numeral: {}", numeral),
+            Self::InvalidFormat => write!(f, "Invalid string format"),
+            Self::ChordNotInCorpus(chord) => {
+                write!(f, "Chord {} not appeared in training set.", chord)
+            }
+            Self::EmptyCorpus => write!(f, "No chords are stored in the generator!"),
+            Self::InvalidIndex(right_index, gen_index) => write!(
+                f,
+                "Right index {} is not greater than {}, the index of chord being generated",
+                right_index, gen_index
+            ),
+            Self::InvalidRange(right_index) => {
+                write!(f, "Right index {} of the range is less than 2", right_index)
+            }
+            Self::InvalidLengthBounds(min_len, max_len) => write!(
+                f,
+                "Minimum length {} is greater than maximum length {}",
+                min_len, max_len
+            ),
+            Self::InvalidProgression(errors) => {
+                write!(f, "Cannot parse chord progression")?;
+                for error in errors {
+                    write!(f, "\n  {}", error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
 
-pub type Error = String;
 pub type Result<T> = result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boxed() {
+        fn parse(value: &str) -> result::Result<Chord, Box<dyn std::error::Error>> {
+            Ok(Chord::try_from(value)?)
+        }
+        assert!(parse("Cm7").is_ok());
+        let err = parse("Xm").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid note character: X");
+        assert_eq!(err.downcast_ref::<Error>(), Some(&Error::InvalidNote('X')));
+    }
+}
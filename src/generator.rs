@@ -1,62 +1,1066 @@
-use std::collections::HashMap;
+use std::{borrow::Cow, collections::{HashMap, VecDeque}, sync::{Mutex, MutexGuard, PoisonError}};
+#[cfg(feature = "serde")]
+use std::io::Read;
+use std::io::{self, Write};
 
 use nalgebra::{DMatrix, DVector};
-use rand::{Rng, distributions::WeightedIndex};
+#[cfg(feature = "rayon")]
+use nalgebra::DVectorViewMut;
+use rand::{Rng, SeedableRng, distributions::WeightedIndex, rngs::StdRng, seq::SliceRandom};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-use crate::{chord::Chord, error::Result};
+use crate::{chord::Chord, error::{Error, Result}, key::Key, note::fifths_distance};
 
+/// Lowest temperature allowed in sampling. Lower temperatures are clamped to this value.
+const MIN_TEMPERATURE: f32 = 1e-6;
+
+/// Maximum number of iterations when calculating the stationary distribution.
+const STATIONARY_MAX_ITERATIONS: usize = 10000;
+
+/// The stationary distribution is considered converged when no probability changes more than this
+/// value in an iteration.
+const STATIONARY_TOLERANCE: f32 = 1e-7;
+
+/// Options of sampling the next chord during generation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SamplingOptions {
+    /// Probabilities are reweighted as `p^(1/temperature)` before sampling. Temperature 1.0 samples
+    /// from the trained probabilities, lower temperature biases toward the most probable chord, and
+    /// higher temperature flattens the distribution.
+    pub temperature: f32,
+    /// Only the smallest set of the most probable chords whose total probability is at least `top_p`
+    /// is sampled from (nucleus sampling), after reweighting by temperature. Top-p 1.0 samples from
+    /// all chords, and top-p 0.0 always picks the most probable chord.
+    pub top_p: f32,
+    /// Never repeat the previous chord immediately, unless no other chord may follow it.
+    pub forbid_immediate_repeat: bool,
+    /// Probabilities of chords whose root is a fifth above or below the root of the previous chord,
+    /// e.g. G to C, are multiplied by `fifth_boost` before sampling. 1.0 leaves them unchanged.
+    pub fifth_boost: f32,
+    /// End the generated sequence with an authentic cadence in the key, i.e. a dominant chord
+    /// followed by a tonic chord, if such chords are in the vocabulary. Only used by `generate` and
+    /// the like, since an iterator has no end.
+    pub cadence: Option<Key>,
+}
+
+impl Default for SamplingOptions {
+    fn default() -> Self {
+        Self { temperature: 1.0, top_p: 1.0, forbid_immediate_repeat: false, fifth_boost: 1.0, cadence: None }
+    }
+}
+
+impl SamplingOptions {
+    /// Reweight a probability vector according to the options. The result is not normalized.
+    fn reweight(&self, probability: &[f32]) -> Vec<f32> {
+        let temperature = self.temperature.max(MIN_TEMPERATURE);
+        // Calculated in log space relative to the maximum probability to avoid underflow when the
+        // temperature is low.
+        let max = probability.iter().copied().fold(0.0, f32::max);
+        let mut weights: Vec<f32> = probability.iter().map(|&p| ((p.ln() - max.ln()) / temperature).exp()).collect();
+        if self.top_p < 1.0 {
+            let total: f32 = weights.iter().sum();
+            let mut order: Vec<usize> = (0..weights.len()).collect();
+            order.sort_by(|&i, &j| weights[j].total_cmp(&weights[i]));
+            let mut cumulative = 0.0;
+            let mut kept = 0;
+            for &i in &order {
+                if kept > 0 && cumulative >= self.top_p * total {
+                    break;
+                }
+                cumulative += weights[i];
+                kept += 1;
+            }
+            for &i in &order[kept..] {
+                weights[i] = 0.0;
+            }
+        }
+        weights
+    }
+}
+
+/// Nonzero entries of a column of the transition matrix or of the occurrences of transitions,
+/// sorted by row.
+#[derive(Clone, Default)]
+struct SparseColumn {
+    indices: Vec<usize>,
+    weights: Vec<f32>,
+}
+
+impl SparseColumn {
+    /// Nonzero entries of a dense column.
+    fn from_dense(column: &[f32]) -> Self {
+        let (indices, weights) = column.iter().enumerate().filter(|(_, &w)| w > 0.0).unzip();
+        Self { indices, weights }
+    }
+
+    /// The column as a dense vector of `size` rows.
+    fn to_dense(&self, size: usize) -> Vec<f32> {
+        let mut ans = vec![0.0; size];
+        for (&i, &w) in self.indices.iter().zip(&self.weights) {
+            ans[i] = w;
+        }
+        ans
+    }
+
+    /// Entry of row `index`, which is 0 if it is not stored.
+    fn get(&self, index: usize) -> f32 {
+        self.indices.binary_search(&index).map_or(0.0, |i| self.weights[i])
+    }
+
+    /// Add `weight` to the entry of row `index`. Zero weights are not stored.
+    fn add(&mut self, index: usize, weight: f32) {
+        match self.indices.binary_search(&index) {
+            Ok(i) => self.weights[i] += weight,
+            Err(_) if weight == 0.0 => {}
+            Err(i) => {
+                self.indices.insert(i, index);
+                self.weights.insert(i, weight);
+            }
+        }
+    }
+}
+
+/// Default number of powers of the transition matrix kept in the cache.
+pub const DEFAULT_CACHE_CAPACITY: usize = 16;
+
+/// Cache of powers of the transition matrix, evicting the least recently used power when more than
+/// `capacity` powers are stored.
+#[derive(Clone)]
+struct PowCache {
+    capacity: usize,
+    powers: HashMap<u32, DMatrix<f32>>,
+    recent: VecDeque<u32>, // exponents from the least to the most recently used
+    scratch: DMatrix<f32>, // buffer reused by `ChordGenerator::transit_pow_into`
+}
+
+impl PowCache {
+    fn new(capacity: usize) -> Self {
+        Self { capacity, powers: HashMap::new(), recent: VecDeque::new(), scratch: DMatrix::zeros(0, 0) }
+    }
+
+    /// Get a cached power and mark it as the most recently used.
+    fn get(&mut self, n: u32) -> Option<&DMatrix<f32>> {
+        let pow_n = self.powers.get(&n)?;
+        self.recent.retain(|&m| m != n);
+        self.recent.push_back(n);
+        Some(pow_n)
+    }
+
+    fn insert(&mut self, n: u32, pow_n: DMatrix<f32>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.powers.insert(n, pow_n).is_some() {
+            self.recent.retain(|&m| m != n);
+        }
+        self.recent.push_back(n);
+        self.shrink();
+    }
+
+    /// Evict the least recently used powers until at most `capacity` powers are stored.
+    fn shrink(&mut self) {
+        while self.recent.len() > self.capacity {
+            if let Some(n) = self.recent.pop_front() {
+                self.powers.remove(&n);
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.powers.clear();
+        self.recent.clear();
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.powers.len()
+    }
+
+    #[cfg(test)]
+    fn is_empty(&self) -> bool {
+        self.powers.is_empty()
+    }
+}
+
+/// `PowCache` behind a lock, so that powers are cached through a shared reference to the generator,
+/// e.g. from several threads.
+struct SharedPowCache(Mutex<PowCache>);
+
+impl SharedPowCache {
+    fn new(capacity: usize) -> Self {
+        Self(Mutex::new(PowCache::new(capacity)))
+    }
+
+    /// Lock the cache. The cache is still used if another thread panicked while holding the lock,
+    /// since it is never left in an inconsistent state.
+    fn lock(&self) -> MutexGuard<'_, PowCache> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Clone for SharedPowCache {
+    fn clone(&self) -> Self {
+        Self(Mutex::new(self.lock().clone()))
+    }
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(from = "GeneratorData", into = "GeneratorData"))]
 pub struct ChordGenerator {
     map_forward: Vec<Chord>,
     map_backward: HashMap<Chord, usize>,
-    transit: DMatrix<f32>,
-    transit_pow_cache: HashMap<u32, DMatrix<f32>>,
+    cooccur: DMatrix<f32>,              // empty if the occurrences of transitions are stored sparse
+    sparse_cooccur: Vec<SparseColumn>, // empty if the occurrences of transitions are stored dense
+    smoothing: f32,
+    cyclic: bool,
+    sparse: bool,
+    transit: DMatrix<f32>,              // empty if the transition matrix is stored sparse
+    sparse_transit: Vec<SparseColumn>, // empty if the transition matrix is stored dense
+    transit_pow_cache: SharedPowCache,
+    order: usize,
+    context_count: HashMap<Vec<usize>, Vec<f32>>,
+    backoff: Option<[f32; 3]>, // weights of order 2, order 1 and unigram distributions if interpolated
+    unigram: Vec<f32>,         // probability of each chord following any chord
+    total: f32,                // total occurrences of transitions, which `unigram` is normalized by
+    decay: f32,                // weight of each transition relative to the next one when training
+    samplers: Vec<Option<WeightedIndex<f32>>>, // sampler of each column with default sampling options
 }
 
 impl ChordGenerator {
+    /// Train a generator from a sequence of chords.
+    ///
+    /// Column `i` of the transition matrix is the probability distribution of the chord following
+    /// chord `i`. If chord `i` is never followed by any chord, its column is left as a uniform
+    /// distribution so that every column is a valid probability vector.
     pub fn new(chord_seq: &[Chord]) -> Self {
-        let mut map_forward = Vec::new();
-        let mut map_backward: HashMap<Chord, usize> = HashMap::new();
-        for chord in chord_seq {
-            if !map_backward.contains_key(&chord) {
-                map_backward.insert(chord.clone(), map_forward.len());
-                map_forward.push(chord.clone());
+        Self::with_smoothing(chord_seq, 0.0)
+    }
+
+    /// Train a generator from a sequence of chords like `new`, treating every trained sequence as a
+    /// loop, i.e. the last chord of the sequence is followed by the first chord.
+    pub fn new_cyclic(chord_seq: &[Chord]) -> Self {
+        let mut generator = Self::empty(0.0, 1, true);
+        generator.train(chord_seq);
+        generator
+    }
+
+    /// Train a generator from a sequence of chords like `new`, storing only the nonzero entries of
+    /// the occurrences of transitions and of the transition matrix. This saves memory for large
+    /// vocabularies where most transitions never appear, since generation only needs one column of
+    /// the matrix at a time. Columns of chords never followed by any chord are still stored as a
+    /// uniform distribution, and smoothing makes every column dense.
+    ///
+    /// Methods that need the whole transition matrix, such as `probability_on` and `generate_range`,
+    /// convert it to a dense matrix, and the cached powers of the matrix are always dense.
+    pub fn new_sparse(chord_seq: &[Chord]) -> Self {
+        let mut generator = Self::empty(0.0, 1, false);
+        generator.sparse = true;
+        generator.train(chord_seq);
+        generator
+    }
+
+    /// Train a generator from a sequence of chords like `new`, adding `alpha` to the number of
+    /// occurrences of every transition before normalization (additive smoothing).
+    ///
+    /// With `alpha > 0` every transition has nonzero probability, so generation can leave any chord.
+    /// The smoothed transition matrix is strictly positive, hence its powers in `transit_pow`
+    /// converge to the stationary distribution and never contain zero entries.
+    pub fn with_smoothing(chord_seq: &[Chord], alpha: f32) -> Self {
+        let mut generator = Self::empty(alpha, 1, false);
+        generator.train(chord_seq);
+        generator
+    }
+
+    /// Train a generator from a sequence of chords like `new`, weighting the occurrence of each
+    /// transition by `decay` to the power of its position from the end of the sequence, so that
+    /// later transitions matter more if `decay < 1`, e.g. when the corpus is ordered by recency.
+    ///
+    /// The last transition has weight 1 and `decay` 1 gives the same generator as `new`. Negative
+    /// values are treated as 0, which keeps only the last transition. The decay is not saved with
+    /// the generator, and applies to each sequence trained later.
+    pub fn new_decayed(chord_seq: &[Chord], decay: f32) -> Self {
+        let mut generator = Self::empty(0.0, 1, false);
+        generator.decay = decay.max(0.0);
+        generator.train(chord_seq);
+        generator
+    }
+
+    /// Train a generator of Markov chain model with order `order` from a sequence of chords, i.e. the
+    /// next chord depends on the previous `order` chords.
+    ///
+    /// Only the contexts of length 2 to `order` that appear in the sequence are stored. When
+    /// generating, a context that never appears falls back to a shorter context, down to the first
+    /// order transition matrix.
+    pub fn with_order(chord_seq: &[Chord], order: usize) -> Self {
+        let mut generator = Self::empty(0.0, order, false);
+        generator.train(chord_seq);
+        generator
+    }
+
+    /// Train a generator of order 2 from a sequence of chords like `with_order`, interpolating the
+    /// distributions of order 2, order 1 and the unigram distribution, i.e. the frequency of each
+    /// chord, with weights `lambdas` in this order.
+    ///
+    /// A context of order 2 that never appears is dropped from the interpolation and the other
+    /// weights are normalized, so that the distribution degrades gracefully to the lower orders.
+    /// The unigram distribution also gives a small probability to transitions never observed.
+    pub fn with_backoff(chord_seq: &[Chord], lambdas: [f32; 3]) -> Self {
+        let mut generator = Self::empty(0.0, 2, false);
+        generator.backoff = Some(lambdas.map(|lambda| lambda.max(0.0)));
+        generator.train(chord_seq);
+        generator
+    }
+
+    /// Builder of a generator with more options than the constructors, e.g. a smoothed generator of
+    /// order 2.
+    pub fn builder() -> ChordGeneratorBuilder {
+        ChordGeneratorBuilder::default()
+    }
+
+    /// Generator with no chords stored.
+    fn empty(smoothing: f32, order: usize, cyclic: bool) -> Self {
+        Self {
+            map_forward: Vec::new(),
+            map_backward: HashMap::new(),
+            cooccur: DMatrix::zeros(0, 0),
+            sparse_cooccur: Vec::new(),
+            smoothing: smoothing.max(0.0),
+            cyclic,
+            sparse: false,
+            transit: DMatrix::zeros(0, 0),
+            sparse_transit: Vec::new(),
+            transit_pow_cache: SharedPowCache::new(DEFAULT_CACHE_CAPACITY),
+            order: order.max(1),
+            context_count: HashMap::new(),
+            backoff: None,
+            unigram: Vec::new(),
+            total: 0.0,
+            decay: 1.0,
+            samplers: Vec::new(),
+        }
+    }
+
+    /// Train the generator with another sequence of chords, e.g. another song. The occurrences of
+    /// transitions are added to the ones already trained, and chords that have not appeared are added
+    /// to the vocabulary.
+    pub fn train(&mut self, chord_seq: &[Chord]) {
+        self.extend_vocabulary(chord_seq);
+        let size = self.map_forward.len();
+        let indices: Vec<usize> = chord_seq.iter().map(|chord| self.map_backward[chord]).collect();
+        // weight of the transition starting at each position, where the closing transition of a cyclic sequence is the last one
+        let transitions = indices.len().saturating_sub(1) + usize::from(self.cyclic && !indices.is_empty());
+        let decay = self.decay;
+        let weight = |position: usize| decay.powi((transitions - 1 - position) as i32);
+        for (i, window) in indices.windows(2).enumerate() {
+            self.add_count(window[1], window[0], weight(i));
+        }
+        if self.cyclic {
+            if let (Some(&first), Some(&last)) = (indices.first(), indices.last()) {
+                self.add_count(first, last, 1.0);
+            }
+        }
+        for len in 2..=self.order {
+            for (i, window) in indices.windows(len + 1).enumerate() {
+                self.context_count.entry(window[..len].to_vec()).or_insert_with(|| vec![0.0; size])[window[len]] += weight(i + len - 1);
+            }
+        }
+        self.update_transit();
+    }
+
+    /// Add a single observed transition from chord `from` to chord `to`, e.g. when the user accepts a
+    /// suggestion. Chords that have not appeared are added to the vocabulary. Unlike `train`, no
+    /// wrap-around transition is added for cyclic generators.
+    ///
+    /// Only the column of `from` in the transition matrix is recalculated, unless new chords are
+    /// added to the vocabulary, which changes the size of every column.
+    pub fn observe(&mut self, from: Chord, to: Chord) {
+        let size = self.map_forward.len();
+        self.extend_vocabulary(&[from.clone(), to.clone()]);
+        let (from_index, to_index) = (self.map_backward[&from], self.map_backward[&to]);
+        self.add_count(to_index, from_index, 1.0);
+        if self.map_forward.len() != size {
+            self.update_transit();
+            return;
+        }
+        self.update_column(from_index);
+        let total = self.total + 1.0;
+        for (i, p) in self.unigram.iter_mut().enumerate() {
+            *p = (*p * self.total + if i == to_index { 1.0 } else { 0.0 }) / total;
+        }
+        self.total = total;
+        self.transit_pow_cache.lock().clear();
+    }
+
+    /// Number of occurrences of chord `to` following chord `from`.
+    fn count(&self, to: usize, from: usize) -> f32 {
+        if self.sparse {
+            self.sparse_cooccur[from].get(to)
+        } else {
+            self.cooccur[(to, from)]
+        }
+    }
+
+    /// Add `weight` occurrences of chord `to` following chord `from`.
+    fn add_count(&mut self, to: usize, from: usize, weight: f32) {
+        if self.sparse {
+            self.sparse_cooccur[from].add(to, weight);
+        } else {
+            self.cooccur[(to, from)] += weight;
+        }
+    }
+
+    /// Occurrences of the chords following chord `i` as a dense vector.
+    fn count_column(&self, i: usize) -> Vec<f32> {
+        if self.sparse {
+            self.sparse_cooccur[i].to_dense(self.map_forward.len())
+        } else {
+            self.cooccur.column(i).iter().copied().collect()
+        }
+    }
+
+    /// Replace the occurrences of the chords following chord `i` with a dense vector.
+    fn set_count_column(&mut self, i: usize, column: Vec<f32>) {
+        if self.sparse {
+            self.sparse_cooccur[i] = SparseColumn::from_dense(&column);
+        } else {
+            self.cooccur.set_column(i, &DVector::from_vec(column));
+        }
+    }
+
+    /// Add the chords that have not appeared to the vocabulary, without any occurrences of transitions.
+    fn extend_vocabulary(&mut self, chords: &[Chord]) {
+        for chord in chords {
+            if !self.map_backward.contains_key(chord) {
+                self.map_backward.insert(chord.clone(), self.map_forward.len());
+                self.map_forward.push(chord.clone());
+            }
+        }
+        let size = self.map_forward.len();
+        if self.sparse {
+            self.sparse_cooccur.resize_with(size, SparseColumn::default);
+        } else {
+            self.cooccur.resize_mut(size, size, 0.0);
+        }
+        for count in self.context_count.values_mut() {
+            count.resize(size, 0.0);
+        }
+    }
+
+    /// Merge with a generator trained on another corpus, e.g. a jazz model and a pop model. The
+    /// vocabulary is the union of both vocabularies, with the chords of `self` first.
+    ///
+    /// For each chord (or context of higher order) that appears in both generators, the probabilities
+    /// of the following chords are blended as `(1 - weight) * p_self + weight * p_other`, while the
+    /// total number of occurrences is the sum of both, so that further training is weighted as usual.
+    /// Chords that appear in only one generator keep their probabilities. Other options such as
+    /// order and smoothing are taken from `self`.
+    pub fn merge(&self, other: &Self, weight: f32) -> Self {
+        let weight = weight.clamp(0.0, 1.0);
+        let mut merged = self.clone();
+        merged.extend_vocabulary(&other.map_forward);
+        let size = merged.map_forward.len();
+        let other_index: Vec<usize> = other.map_forward.iter().map(|chord| merged.map_backward[chord]).collect();
+        let reindex = |count: &[f32]| {
+            let mut ans = vec![0.0; size];
+            for (&i, &c) in other_index.iter().zip(count) {
+                ans[i] = c;
+            }
+            ans
+        };
+        for (i, &merged_i) in other_index.iter().enumerate() {
+            let blended = blend_counts(&merged.count_column(merged_i), &reindex(&other.count_column(i)), weight);
+            merged.set_count_column(merged_i, blended);
+        }
+        for (context, count) in &other.context_count {
+            let context: Vec<usize> = context.iter().map(|&i| other_index[i]).collect();
+            let merged_count = merged.context_count.entry(context).or_insert_with(|| vec![0.0; size]);
+            *merged_count = blend_counts(merged_count, &reindex(count), weight);
+        }
+        merged.update_transit();
+        merged
+    }
+
+    /// Recalculate the transition matrix from the occurrences of transitions, and invalidate the
+    /// cached powers of the old transition matrix.
+    fn update_transit(&mut self) {
+        let size = self.map_forward.len();
+        if self.sparse {
+            self.sparse_transit.resize_with(size, SparseColumn::default);
+        } else {
+            self.transit = DMatrix::zeros(size, size);
+        }
+        self.samplers.resize_with(size, || None);
+        for i in 0..size {
+            self.update_column(i);
+        }
+        let mut following = vec![0.0; size];
+        if self.sparse {
+            for column in &self.sparse_cooccur {
+                for (&j, &count) in column.indices.iter().zip(&column.weights) {
+                    following[j] += count;
+                }
+            }
+        } else {
+            following = self.cooccur.column_sum().iter().copied().collect();
+        }
+        self.total = following.iter().sum();
+        self.unigram = if self.total > 0.0 {
+            following.iter().map(|&count| count / self.total).collect()
+        } else {
+            vec![1.0 / size as f32; size]
+        };
+        self.transit_pow_cache.lock().clear();
+    }
+
+    /// Recalculate column `i` of the transition matrix and its sampler from the occurrences of the
+    /// chords following chord `i`. Sparse columns are normalized from the stored occurrences without
+    /// building a dense column, unless the generator is smoothed.
+    fn update_column(&mut self, i: usize) {
+        let size = self.map_forward.len();
+        if self.sparse {
+            let counts = &self.sparse_cooccur[i];
+            let sum = counts.weights.iter().sum::<f32>() + self.smoothing * size as f32;
+            self.sparse_transit[i] = if sum <= 0.0 {
+                SparseColumn { indices: (0..size).collect(), weights: vec![1.0 / size as f32; size] }
+            } else if self.smoothing > 0.0 {
+                let mut column = counts.to_dense(size);
+                column.iter_mut().for_each(|count| *count = (*count + self.smoothing) / sum);
+                SparseColumn::from_dense(&column)
+            } else {
+                SparseColumn { indices: counts.indices.clone(), weights: counts.weights.iter().map(|&count| count / sum).collect() }
+            };
+        } else {
+            let mut column = self.cooccur.column(i).add_scalar(self.smoothing);
+            let sum = column.sum();
+            if sum > 0.0 {
+                column /= sum;
+            } else {
+                column.fill(1.0 / size as f32);
+            }
+            self.transit.set_column(i, &column);
+        }
+        // reweighted like sampling in `ChordIter` so that both draw the same chords from the same seed
+        let options = SamplingOptions::default();
+        let column = if self.sparse { &self.sparse_transit[i].weights[..] } else { &self.transit.as_slice()[i * size..(i + 1) * size] };
+        self.samplers[i] = WeightedIndex::new(options.reweight(column)).ok();
+    }
+
+    /// Set the maximum number of powers of the transition matrix kept in the cache, evicting the
+    /// least recently used powers if more are stored. The default capacity is
+    /// `DEFAULT_CACHE_CAPACITY`, and capacity 0 disables the cache. The capacity is not saved with
+    /// the generator.
+    pub fn set_cache_capacity(&mut self, capacity: usize) {
+        let mut cache = self.transit_pow_cache.lock();
+        cache.capacity = capacity;
+        cache.shrink();
+    }
+
+    /// Whether the transition matrix is stored sparse.
+    pub fn is_sparse(&self) -> bool {
+        self.sparse
+    }
+
+    /// The transition matrix as a dense matrix, which is converted from the sparse storage if the
+    /// generator is sparse.
+    fn dense_transit(&self) -> Cow<'_, DMatrix<f32>> {
+        if !self.sparse {
+            return Cow::Borrowed(&self.transit);
+        }
+        let size = self.map_forward.len();
+        let mut transit = DMatrix::zeros(size, size);
+        for (i, column) in self.sparse_transit.iter().enumerate() {
+            for (&j, &p) in column.indices.iter().zip(&column.weights) {
+                transit[(j, i)] = p;
+            }
+        }
+        Cow::Owned(transit)
+    }
+
+    /// All chords known by the generator, in the order they are stored, which is also the order of
+    /// rows and columns of the transition matrix.
+    pub fn vocabulary(&self) -> &[Chord] {
+        &self.map_forward
+    }
+
+    /// Whether the chord is known by the generator, i.e. appears in the training set.
+    pub fn contains(&self, chord: &Chord) -> bool {
+        self.map_backward.contains_key(chord)
+    }
+
+    /// Order of the Markov chain model.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// Weights of the chord following the chords with index `history`, using the longest known
+    /// context of at most `order` chords, or the interpolated distribution of a back-off model. The
+    /// weights are not necessarily normalized.
+    ///
+    /// Returns the index of chords of each weight if only part of the chords are listed, or `None`
+    /// if the weights of all chords are listed in order.
+    fn next_probability(&self, history: &[usize]) -> (Option<&[usize]>, Cow<'_, [f32]>) {
+        if let Some(lambdas) = self.backoff {
+            return (None, Cow::Owned(self.backoff_probability(history, lambdas)));
+        }
+        for len in (2..=self.order.min(history.len())).rev() {
+            if let Some(count) = self.context_count.get(&history[history.len() - len..]) {
+                return (None, Cow::Borrowed(count));
+            }
+        }
+        let last = history[history.len() - 1];
+        if self.sparse {
+            let column = &self.sparse_transit[last];
+            return (Some(&column.indices), Cow::Borrowed(&column.weights));
+        }
+        let size = self.map_forward.len();
+        (None, Cow::Borrowed(&self.transit.as_slice()[last * size..(last + 1) * size]))
+    }
+
+    /// Sampler of the chord following the chords with index `history` with default sampling options,
+    /// if the first order transition matrix is used, together with the indices of sampled chords if
+    /// the transition matrix is stored sparse. The samplers are built once when the transition matrix
+    /// is updated.
+    fn cached_sampler(&self, history: &[usize]) -> Option<(Option<&[usize]>, &WeightedIndex<f32>)> {
+        if self.backoff.is_some()
+            || (2..=self.order.min(history.len())).any(|len| {
+                self.context_count
+                    .contains_key(&history[history.len() - len..])
+            })
+        {
+            return None;
+        }
+        let last = *history.last()?;
+        let indices = if self.sparse { Some(&self.sparse_transit[last].indices[..]) } else { None };
+        Some((indices, self.samplers[last].as_ref()?))
+    }
+
+    /// Probability of the chord following the chords with index `history` in a back-off model,
+    /// interpolating the distributions of order 2, order 1 and the unigram distribution.
+    fn backoff_probability(&self, history: &[usize], lambdas: [f32; 3]) -> Vec<f32> {
+        let size = self.map_forward.len();
+        let last = history[history.len() - 1];
+        let second_order = history.len().checked_sub(2).and_then(|start| self.context_count.get(&history[start..])).map(|count| {
+            let sum: f32 = count.iter().sum();
+            count.iter().map(|c| c / sum).collect::<Vec<f32>>()
+        });
+        let first_order: Vec<f32> = if self.sparse {
+            let mut column = vec![0.0; size];
+            for (&j, &p) in self.sparse_transit[last].indices.iter().zip(&self.sparse_transit[last].weights) {
+                column[j] = p;
+            }
+            column
+        } else {
+            self.transit.column(last).iter().copied().collect()
+        };
+        let mut ans = vec![0.0; size];
+        let mut total = 0.0;
+        for (lambda, distribution) in lambdas.into_iter().zip([second_order.as_deref(), Some(&first_order[..]), Some(&self.unigram[..])]) {
+            if let Some(distribution) = distribution {
+                for (a, p) in ans.iter_mut().zip(distribution) {
+                    *a += lambda * p;
+                }
+                total += lambda;
+            }
+        }
+        if total > 0.0 {
+            ans.iter_mut().for_each(|a| *a /= total);
+        }
+        ans
+    }
+
+    /// Write the transition matrix as CSV. The header row lists all chords in the order they are
+    /// stored, and each following row lists the probabilities of the chords following a chord.
+    pub fn to_csv<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        write!(out, "from\\to")?;
+        for chord in &self.map_forward {
+            write!(out, ",{}", chord)?;
+        }
+        writeln!(out)?;
+        let transit = self.dense_transit();
+        for (i, chord) in self.map_forward.iter().enumerate() {
+            write!(out, "{}", chord)?;
+            for p in transit.column(i).iter() {
+                write!(out, ",{}", p)?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    /// Transition matrix as an aligned table for debugging, laid out like `to_csv`, with each row
+    /// listing the probabilities of the chords following a chord to two decimals.
+    pub fn display_matrix(&self) -> String {
+        let labels: Vec<String> = self.map_forward.iter().map(|chord| chord.to_string()).collect();
+        let first_width = labels.iter().map(|label| label.chars().count()).chain(["from\\to".len()]).max().unwrap_or(0);
+        let width = labels.iter().map(|label| label.chars().count()).chain(["0.00".len()]).max().unwrap_or(0);
+        let mut ans = format!("{:<first_width$}", "from\\to");
+        for label in &labels {
+            ans += &format!(" {:>width$}", label);
+        }
+        ans.push('\n');
+        let transit = self.dense_transit();
+        for (i, label) in labels.iter().enumerate() {
+            ans += &format!("{:<first_width$}", label);
+            for p in transit.column(i).iter() {
+                ans += &format!(" {:>width$.2}", p);
             }
+            ans.push('\n');
         }
-        let mut cooccur: DMatrix<f32> = DMatrix::zeros(map_forward.len(), map_forward.len());
-        for i in 1..chord_seq.len() {
-            cooccur[(map_backward[&chord_seq[i]], map_backward[&chord_seq[i-1]])] += 1.0;
+        ans
+    }
+
+    /// GraphViz DOT graph of the Markov chain. Each chord is a node, and each transition with
+    /// probability above `threshold` is an edge labeled and weighted by its probability.
+    pub fn to_dot(&self, threshold: f32) -> String {
+        let mut ans = String::from("digraph {\n");
+        for (i, chord) in self.map_forward.iter().enumerate() {
+            ans += &format!("    {} [label=\"{}\"];\n", i, chord);
         }
-        cooccur[(map_backward[&chord_seq[0]], map_backward[&chord_seq[chord_seq.len() - 1]])] += 1.0;
-        for i in 0..map_forward.len() {
-            cooccur.set_column(i, &(cooccur.column(i) / cooccur.column(i).sum()));
+        let transit = self.dense_transit();
+        for i in 0..self.map_forward.len() {
+            for (j, &p) in transit.column(i).iter().enumerate() {
+                if p > threshold {
+                    ans += &format!("    {} -> {} [label=\"{:.2}\", weight={}];\n", i, j, p, p);
+                }
+            }
         }
-        Self { map_forward, map_backward, transit: cooccur, transit_pow_cache: HashMap::new() }
+        ans + "}\n"
+    }
+
+    /// Save the trained generator as JSON.
+    #[cfg(feature = "serde")]
+    pub fn save<W: Write>(&self, writer: W) -> serde_json::Result<()> {
+        serde_json::to_writer(writer, self)
+    }
+
+    /// Load a generator saved by `save`.
+    #[cfg(feature = "serde")]
+    pub fn load<R: Read>(reader: R) -> serde_json::Result<Self> {
+        serde_json::from_reader(reader)
     }
 
     /// Generate a sequence of chords with length `number` with plain Markov chain model, or return
-    /// an error.
+    /// an error, e.g. `Error::ChordNotInCorpus` if `init_chord` is not in the training set.
+    ///
+    /// For models with order higher than 1, each chord is generated from the previous chords
+    /// generated, starting from `init_chord`.
     pub fn generate(&self, init_chord: Chord, number: usize, rng: &mut impl Rng) -> Result<Vec<Chord>> {
+        self.generate_with_options(init_chord, number, SamplingOptions::default(), rng)
+    }
+
+    /// Generate a sequence of chords with length `number` like `generate`, using a random number
+    /// generator seeded with `seed`. The same seed and the same model always give the same sequence.
+    pub fn generate_seeded(&self, init_chord: Chord, number: usize, seed: u64) -> Result<Vec<Chord>> {
+        self.generate(init_chord, number, &mut StdRng::seed_from_u64(seed))
+    }
+
+    /// Generate a sequence of chords with length `number` like `generate`, sampling each chord with
+    /// the given options.
+    pub fn generate_with_options(
+        &self,
+        init_chord: Chord,
+        number: usize,
+        options: SamplingOptions,
+        rng: &mut impl Rng,
+    ) -> Result<Vec<Chord>> {
+        if !self.contains(&init_chord) {
+            return Err(Error::ChordNotInCorpus(init_chord));
+        }
+        let cadence_len = if options.cadence.is_some() { number.min(2) } else { 0 };
+        let mut iter = self.iter_with_options(init_chord, options, rng);
+        let mut ans: Vec<Chord> = iter.by_ref().take(number - cadence_len).collect();
+        if let Some(key) = options.cadence {
+            if cadence_len == 2 {
+                ans.extend(iter.next_matching(|chord| key.is_dominant(chord)));
+            }
+            if cadence_len >= 1 {
+                ans.extend(iter.next_matching(|chord| key.is_tonic(chord)));
+            }
+        }
+        if ans.len() < number {
+            return Err(Error::EmptyCorpus);
+        }
+        Ok(ans)
+    }
+
+    /// Generate a sequence of chords like `generate_with_options`, with length between `min_len` and
+    /// `max_len` (inclusive) rather than a fixed length, e.g. to generate phrases of varying length.
+    ///
+    /// Sampling stops at the first chord from index `min_len - 1` on that is an absorbing state or
+    /// that ends an authentic cadence in `options.cadence`, i.e. a tonic chord right after a
+    /// dominant chord, and at `max_len` chords otherwise. No cadence is forced at the end. Returns
+    /// `Error::InvalidLengthBounds` if `min_len > max_len`.
+    pub fn generate_bounded(
+        &self,
+        init_chord: Chord,
+        min_len: usize,
+        max_len: usize,
+        options: SamplingOptions,
+        rng: &mut impl Rng,
+    ) -> Result<Vec<Chord>> {
+        if min_len > max_len {
+            return Err(Error::InvalidLengthBounds(min_len, max_len));
+        }
+        if !self.contains(&init_chord) {
+            return Err(Error::ChordNotInCorpus(init_chord));
+        }
+        let absorbing = self.absorbing_states();
+        let mut previous = init_chord.clone();
+        let mut iter = self.iter_with_options(init_chord, options, rng);
+        let mut ans = Vec::with_capacity(max_len);
+        while ans.len() < max_len {
+            let chord = iter.next().ok_or(Error::EmptyCorpus)?;
+            let cadence = options.cadence.is_some_and(|key| key.is_dominant(&previous) && key.is_tonic(&chord));
+            let stop = ans.len() + 1 >= min_len && (cadence || absorbing.contains(&chord));
+            ans.push(chord.clone());
+            if stop {
+                break;
+            }
+            previous = chord;
+        }
+        Ok(ans)
+    }
+
+    /// Generate `count` independent sequences of chords with length `number` like `generate`, all
+    /// starting from `init_chord`. The distribution of each context is built once and reused by all
+    /// sequences.
+    pub fn generate_batch(&self, init_chord: Chord, number: usize, count: usize, rng: &mut impl Rng) -> Result<Vec<Vec<Chord>>> {
+        let init = *self.map_backward.get(&init_chord).ok_or(Error::ChordNotInCorpus(init_chord))?;
+        let mut distributions = HashMap::new();
+        let mut batch = Vec::with_capacity(count);
+        for _ in 0..count {
+            let mut history = vec![init];
+            let mut ans = Vec::with_capacity(number);
+            for _ in 0..number {
+                if !distributions.contains_key(&history) {
+                    let (indices, probability) = self.next_probability(&history);
+                    let distr = WeightedIndex::new(&*probability).map_err(|_| Error::EmptyCorpus)?;
+                    distributions.insert(history.clone(), (indices, distr));
+                }
+                let (indices, distr) = &distributions[&history];
+                let sampled = rng.sample(distr);
+                let gen = indices.map_or(sampled, |indices| indices[sampled]);
+                history.push(gen);
+                if history.len() > self.order {
+                    history.remove(0);
+                }
+                ans.push(self.map_forward[gen].clone());
+            }
+            batch.push(ans);
+        }
+        Ok(batch)
+    }
+
+    /// Generate a sequence of chords with length `number` like `generate`, keeping only the diatonic
+    /// chords of `key`, i.e. the chords whose notes are all in the scale of `key`.
+    ///
+    /// The probabilities of the other chords are set to zero before sampling. If no diatonic chord
+    /// may follow the previous chords, the next chord is sampled from all chords instead.
+    pub fn generate_in_key(&self, init_chord: Chord, number: usize, key: Key, rng: &mut impl Rng) -> Result<Vec<Chord>> {
+        let scale = key.scale();
+        let diatonic: Vec<bool> = self.map_forward.iter().map(|chord| chord.notes().iter().all(|note| scale.contains(note))).collect();
+        let mut history = vec![*self.map_backward.get(&init_chord).ok_or(Error::ChordNotInCorpus(init_chord))?];
         let mut ans = Vec::with_capacity(number);
-        let mut cur_chord_index = self.map_backward[&init_chord];
         for _ in 0..number {
-            let column = self.transit.column(cur_chord_index);
-            let probability = column.as_slice();
-            if let Ok(distr) = WeightedIndex::new(probability) {
-                let gen = rng.sample(distr);
-                let gened_chord = self.map_forward[gen].clone();
-                ans.push(gened_chord.clone());
-                cur_chord_index = gen;
-            } else {
-                return Err("No chords are stored in the gererator!".to_string());
+            let (indices, probability) = self.next_probability(&history);
+            let index_of = |i: usize| indices.map_or(i, |indices| indices[i]);
+            let filtered: Vec<f32> = probability.iter().enumerate().map(|(i, &p)| if diatonic[index_of(i)] { p } else { 0.0 }).collect();
+            let distr = WeightedIndex::new(filtered).or_else(|_| WeightedIndex::new(&*probability)).map_err(|_| Error::EmptyCorpus)?;
+            let gen = index_of(rng.sample(distr));
+            history.push(gen);
+            if history.len() > self.order {
+                history.remove(0);
+            }
+            ans.push(self.map_forward[gen].clone());
+        }
+        Ok(ans)
+    }
+
+    /// Generate a sequence of chords with length `number` like `generate`, sampling each chord from
+    /// a blend of the distributions of `self` and `other`, e.g. a jazz model and a pop model.
+    ///
+    /// Only chords in both vocabularies are generated. At each step, the distributions of the chord
+    /// following the previous chords in both generators are restricted to the shared chords,
+    /// normalized, and blended as `(1 - alpha) * p_self + alpha * p_other`. Unlike `merge`, both
+    /// generators are kept unchanged, so `alpha` may differ from call to call.
+    pub fn blend_generate(&self, other: &Self, alpha: f32, init_chord: Chord, number: usize, rng: &mut impl Rng) -> Result<Vec<Chord>> {
+        let alpha = alpha.clamp(0.0, 1.0);
+        let shared: Vec<(usize, usize)> = self
+            .map_forward
+            .iter()
+            .enumerate()
+            .filter_map(|(i, chord)| Some((i, *other.map_backward.get(chord)?)))
+            .collect();
+        let init = match (self.map_backward.get(&init_chord), other.map_backward.get(&init_chord)) {
+            (Some(&i), Some(&j)) => (i, j),
+            _ => return Err(Error::ChordNotInCorpus(init_chord)),
+        };
+        // distribution of the next shared chord in a generator, or all zeros if no shared chord may follow
+        let restricted = |generator: &Self, history: &[usize], index: fn(&(usize, usize)) -> usize| {
+            let (indices, probability) = generator.next_probability(history);
+            let mut dense = vec![0.0; generator.map_forward.len()];
+            for (i, &p) in probability.iter().enumerate() {
+                dense[indices.map_or(i, |indices| indices[i])] = p;
             }
+            let ans: Vec<f32> = shared.iter().map(|pair| dense[index(pair)]).collect();
+            let sum: f32 = ans.iter().sum();
+            if sum > 0.0 { ans.into_iter().map(|p| p / sum).collect() } else { ans }
+        };
+        let (mut self_history, mut other_history) = (vec![init.0], vec![init.1]);
+        let mut ans = Vec::with_capacity(number);
+        for _ in 0..number {
+            let p_self = restricted(self, &self_history, |pair| pair.0);
+            let p_other = restricted(other, &other_history, |pair| pair.1);
+            let blended: Vec<f32> = p_self.iter().zip(&p_other).map(|(a, b)| (1.0 - alpha) * a + alpha * b).collect();
+            let distr = WeightedIndex::new(blended).map_err(|_| Error::EmptyCorpus)?;
+            let (i, j) = shared[rng.sample(distr)];
+            for (history, gen, order) in [(&mut self_history, i, self.order), (&mut other_history, j, other.order)] {
+                history.push(gen);
+                if history.len() > order {
+                    history.remove(0);
+                }
+            }
+            ans.push(self.map_forward[i].clone());
         }
         Ok(ans)
     }
 
+    /// Lazily generate chords following `init_chord` with plain Markov chain model. The iterator
+    /// never ends unless no chords are stored in the generator, and is empty if `init_chord` is not
+    /// in the training set.
+    pub fn iter<R: Rng>(&self, init_chord: Chord, rng: R) -> ChordIter<'_, R> {
+        self.iter_with_options(init_chord, SamplingOptions::default(), rng)
+    }
+
+    /// Lazily generate chords like `iter`, sampling each chord with the given options.
+    pub fn iter_with_options<R: Rng>(&self, init_chord: Chord, options: SamplingOptions, rng: R) -> ChordIter<'_, R> {
+        ChordIter { generator: self, history: self.map_backward.get(&init_chord).copied().into_iter().collect(), options, rng }
+    }
+
+    /// Probability that chord `from` is followed by chord `to` in the first order Markov chain, or an
+    /// error if either chord is not in the training set.
+    pub fn transition_probability(&self, from: Chord, to: Chord) -> Result<f32> {
+        let from_index = *self.map_backward.get(&from).ok_or(Error::ChordNotInCorpus(from))?;
+        let to_index = *self.map_backward.get(&to).ok_or(Error::ChordNotInCorpus(to))?;
+        if self.sparse {
+            let column = &self.sparse_transit[from_index];
+            Ok(column.indices.iter().position(|&i| i == to_index).map_or(0.0, |k| column.weights[k]))
+        } else {
+            Ok(self.transit[(to_index, from_index)])
+        }
+    }
+
+    /// The most probable chord following `current`. Among chords of equal probability, the smallest
+    /// one in the order of `Chord` is returned.
+    pub fn most_likely_next(&self, current: Chord) -> Result<Chord> {
+        let index = *self.map_backward.get(&current).ok_or(Error::ChordNotInCorpus(current))?;
+        let (indices, probability) = self.next_probability(&[index]);
+        probability.iter().enumerate().map(|(i, &p)| (&self.map_forward[indices.map_or(i, |indices| indices[i])], p))
+            .min_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(b.0)))
+            .map(|(chord, _)| chord.clone())
+            .ok_or(Error::EmptyCorpus)
+    }
+
+    /// The `k` most probable chords following `current` with their probabilities, sorted from the
+    /// most probable. Chords of equal probability are sorted in the order they are stored, and
+    /// chords that never follow `current` are not listed, so fewer than `k` chords are returned if
+    /// `k` is larger than the number of chords that may follow.
+    pub fn top_k_next(&self, current: Chord, k: usize) -> Result<Vec<(Chord, f32)>> {
+        let index = *self.map_backward.get(&current).ok_or(Error::ChordNotInCorpus(current))?;
+        let (indices, probability) = self.next_probability(&[index]);
+        let mut candidates: Vec<(usize, f32)> = probability
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| (indices.map_or(i, |indices| indices[i]), p))
+            .filter(|&(_, p)| p > 0.0)
+            .collect();
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        Ok(candidates.into_iter().take(k).map(|(i, p)| (self.map_forward[i].clone(), p)).collect())
+    }
+
+    /// Long-run probability of each chord in the first order Markov chain, i.e. the vector `v` with
+    /// `transit * v = v`, in the order chords are stored.
+    ///
+    /// It is calculated by power iteration from the uniform distribution on the lazy chain
+    /// `(I + transit) / 2`, which has the same stationary distribution and also converges when the
+    /// chain is periodic, e.g. C G C G. If the chain is not irreducible, e.g. the corpus consists of
+    /// songs without chords in common, the stationary distribution is not unique and the result
+    /// depends on the uniform starting point.
+    pub fn stationary_distribution(&self) -> DVector<f32> {
+        let size = self.map_forward.len();
+        if size == 0 {
+            return DVector::zeros(0);
+        }
+        let transit = self.dense_transit();
+        let mut ans = DVector::from_element(size, 1.0 / size as f32);
+        for _ in 0..STATIONARY_MAX_ITERATIONS {
+            let next = (transit.as_ref() * &ans + &ans) / 2.0;
+            let diff = (&next - &ans).abs().max();
+            ans = next;
+            if diff < STATIONARY_TOLERANCE {
+                break;
+            }
+        }
+        &ans / ans.sum()
+    }
+
+    /// Average conditional entropy in bits of the chord following each chord, weighted by the
+    /// stationary distribution (see `stationary_distribution`). A low entropy means the trained
+    /// progressions are predictable, while the entropy of a chain with `n` chords where every
+    /// transition is equally likely is `log2(n)`.
+    pub fn entropy(&self) -> f32 {
+        let stationary = self.stationary_distribution();
+        let transit = self.dense_transit();
+        stationary.iter().enumerate().map(|(i, &weight)| {
+            let column_entropy: f32 = transit.column(i).iter().filter(|&&p| p > 0.0).map(|&p| -p * p.log2()).sum();
+            weight * column_entropy
+        }).sum()
+    }
+
+    /// Chords that are always followed by themselves in the first order Markov chain, in the order
+    /// they are stored. Once generated, such a chord repeats forever, which usually means that the
+    /// training set is too small or lacks smoothing.
+    pub fn absorbing_states(&self) -> Vec<Chord> {
+        let transit = self.dense_transit();
+        self.map_forward.iter().enumerate().filter(|&(i, _)| transit[(i, i)] == 1.0).map(|(_, chord)| chord.clone()).collect()
+    }
+
+    /// Number of distinct chords leading into and out of each chord in the training set, as
+    /// `(chord, in_degree, out_degree)`. Only observed transitions are counted, regardless of
+    /// smoothing.
+    pub fn degree_stats(&self) -> Vec<(Chord, usize, usize)> {
+        self.map_forward.iter().enumerate().map(|(i, chord)| {
+            let in_degree = (0..self.map_forward.len()).filter(|&j| self.count(i, j) > 0.0).count();
+            let out_degree = self.count_column(i).iter().filter(|&&count| count > 0.0).count();
+            (chord.clone(), in_degree, out_degree)
+        }).collect()
+    }
+
+    /// Log likelihood of the chord sequence under the first order Markov chain model, i.e. the sum of
+    /// the logarithm of the probability of each transition in the sequence. Returns negative
+    /// infinity if any transition never appears, or an error if any chord is not in the training
+    /// set.
+    pub fn log_likelihood(&self, chords: &[Chord]) -> Result<f64> {
+        let indices = chords
+            .iter()
+            .map(|chord| {
+                self.map_backward
+                    .get(chord)
+                    .copied()
+                    .ok_or_else(|| Error::ChordNotInCorpus(chord.clone()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let transit = self.dense_transit();
+        Ok(indices.windows(2).map(|window| (transit[(window[1], window[0])] as f64).ln()).sum())
+    }
+
     /// Give the chord at index `0` and `right_index`, returns the probability vector of chord at `gen_index`.
-    pub fn probability_on(&mut self, left_chord: Chord, right_chord: Chord, right_index: usize, gen_index: usize) -> Result<DVector<f32>> {
+    pub fn probability_on(&self, left_chord: Chord, right_chord: Chord, right_index: usize, gen_index: usize) -> Result<DVector<f32>> {
         if right_index <= gen_index {
-            return Err(format!("Right index {} is not greater than {}, the index of chord being generated", right_index, gen_index));
+            return Err(Error::InvalidIndex(right_index, gen_index));
         }
         match (self.map_backward.get(&left_chord), self.map_backward.get(&right_chord)) {
             (Some(&l_ch), Some(&r_ch)) => {
@@ -66,18 +1070,18 @@ impl ChordGenerator {
                 Ok(p_g.column(l_ch).component_mul(&p_r_g.row(r_ch).transpose()) / p_r[(r_ch, l_ch)])
             },
             (None, _) => {
-                Err(format!("Chord {} not appeared in training set.", left_chord))
+                Err(Error::ChordNotInCorpus(left_chord))
             },
             (_, None) => {
-                Err(format!("Chord {} not appeared in training set.", right_chord))
+                Err(Error::ChordNotInCorpus(right_chord))
             },
         }
     }
 
     /// Give the chord at index -1 and `ans_vec.len()`, fill the mutable chord array's index 0 (inclusive)
     /// to `ans_vec.len() - 1` (inclusive) with randomly generated chords or returns an error.
-    fn generate_fill(&mut self, ans_vec: &mut [Chord], left_chord: Chord, right_chord: Chord, rng: &mut impl Rng) -> Result<()> {
-        if ans_vec.len() == 0 {
+    fn generate_fill(&self, ans_vec: &mut [Chord], left_chord: Chord, right_chord: Chord, rng: &mut impl Rng) -> Result<()> {
+        if ans_vec.is_empty() {
             return Ok(())
         }
         let mid = ans_vec.len() / 2;
@@ -93,53 +1097,424 @@ impl ChordGenerator {
             self.generate_fill(&mut ans_vec[(mid + 1)..], gened_chord.clone(), right_chord.clone(), rng)?;
             Ok(())
         } else {
-            Err("No chords are stored in the gererator!".to_string())
+            Err(Error::EmptyCorpus)
         }
     }
 
     /// Give the chord at index 0 and `right_index`, returns a randomly generated sequence between
     /// index 1 (inclusive) and `right_index - 1` (inclusive) or returns an error.
-    pub fn generate_range(&mut self, left_chord: Chord, right_chord: Chord, right_index: usize, rng: &mut impl Rng) -> Result<Vec<Chord>> {
+    ///
+    /// Returns `Error::InvalidRange` if `right_index < 2`, and `Error::ChordNotInCorpus` if either end
+    /// chord is not in the training set.
+    pub fn generate_range(&self, left_chord: Chord, right_chord: Chord, right_index: usize, rng: &mut impl Rng) -> Result<Vec<Chord>> {
+        if right_index < 2 {
+            return Err(Error::InvalidRange(right_index));
+        }
+        for chord in [&left_chord, &right_chord] {
+            if !self.map_backward.contains_key(chord) {
+                return Err(Error::ChordNotInCorpus(chord.clone()));
+            }
+        }
         let mut ans = vec![Chord::default(); right_index - 2];
         self.generate_fill(&mut ans, left_chord, right_chord, rng)?;
         Ok(ans)
     }
 
-    /// Get the nth power of transition matrix.
-    /// 
-    /// If the nth power is cached, directly return the cached matrix. Otherwise, calculate it using
-    /// binary exponent algorithm.
-    fn transit_pow(&mut self, n: u32) -> DMatrix<f32> {
-        if n == 1 {
-            self.transit.clone()
-        } else if let Some(pow_n) = self.transit_pow_cache.get(&n) {
-            pow_n.clone()
-        } else {
-            let pow_n_2 = self.transit_pow(n / 2);
-            let ans = if n & 1 > 0 {
-                pow_n_2.clone() * pow_n_2 * &self.transit
-            } else {
-                pow_n_2.clone() * pow_n_2
-            };
-            self.transit_pow_cache.insert(n, ans.clone());
-            ans
+    /// Generate up to `k` distinct sequences between the chords at index 0 and `right_index` like
+    /// `generate_range`, e.g. for a user to pick from, or returns an error.
+    ///
+    /// Sequences are drawn at most `10 * k` times (saturating at `usize::MAX`), so fewer than `k`
+    /// sequences are returned if there are not enough possible sequences or some are much more likely
+    /// than the others. The result grows with the sequences found rather than being allocated for
+    /// `k` up front, so a large `k` only costs the draws it takes.
+    pub fn generate_range_candidates(
+        &self,
+        left_chord: Chord,
+        right_chord: Chord,
+        right_index: usize,
+        k: usize,
+        rng: &mut impl Rng,
+    ) -> Result<Vec<Vec<Chord>>> {
+        let mut ans: Vec<Vec<Chord>> = Vec::new();
+        for _ in 0..10usize.saturating_mul(k) {
+            if ans.len() == k {
+                break;
+            }
+            let candidate = self.generate_range(left_chord.clone(), right_chord.clone(), right_index, rng)?;
+            if !ans.contains(&candidate) {
+                ans.push(candidate);
+            }
         }
+        Ok(ans)
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
 
-    #[test]
-    fn test_init() {
+    /// Give the chord at index 0 and `right_index`, returns the most probable sequence between them
+    /// like `generate_range`, or returns an error.
+    ///
+    /// The sequence is found with beam search in the first order Markov chain, keeping the `beam`
+    /// most probable partial sequences ending in different chords at each step, so a beam at least
+    /// as large as the vocabulary always finds the most probable sequence (Viterbi algorithm).
+    /// Returns `Error::EmptyCorpus` if no sequence found connects both end chords.
+    pub fn best_range(&self, left_chord: Chord, right_chord: Chord, right_index: usize, beam: usize) -> Result<Vec<Chord>> {
+        if right_index < 2 {
+            return Err(Error::InvalidRange(right_index));
+        }
+        let left = *self.map_backward.get(&left_chord).ok_or(Error::ChordNotInCorpus(left_chord))?;
+        let right = *self.map_backward.get(&right_chord).ok_or(Error::ChordNotInCorpus(right_chord))?;
+        let transit = self.dense_transit();
+        // log probability and chord indices of each partial sequence, starting from the left chord
+        let mut paths: Vec<(f64, Vec<usize>)> = vec![(0.0, vec![left])];
+        for _ in 2..right_index {
+            let mut best: HashMap<usize, (f64, Vec<usize>)> = HashMap::new();
+            for (score, path) in &paths {
+                let last = path[path.len() - 1];
+                for (next, &p) in transit.column(last).iter().enumerate() {
+                    let score = score + (p as f64).ln();
+                    if p > 0.0 && best.get(&next).is_none_or(|(best_score, _)| score > *best_score) {
+                        best.insert(next, (score, path.iter().copied().chain([next]).collect()));
+                    }
+                }
+            }
+            paths = best.into_values().collect();
+            paths.sort_by(|(a, path_a), (b, path_b)| b.total_cmp(a).then_with(|| path_a.cmp(path_b)));
+            paths.truncate(beam.max(1));
+        }
+        paths.into_iter()
+            .filter(|(_, path)| transit[(right, path[path.len() - 1])] > 0.0)
+            .map(|(score, path)| (score + (transit[(right, path[path.len() - 1])] as f64).ln(), path))
+            .max_by(|(a, path_a), (b, path_b)| a.total_cmp(b).then_with(|| path_b.cmp(path_a)))
+            .map(|(_, path)| path[1..].iter().map(|&i| self.map_forward[i].clone()).collect())
+            .ok_or(Error::EmptyCorpus)
+    }
+
+    /// Write the nth power of transition matrix into `out`, which is resized if its shape does not
+    /// match the transition matrix. A cached power is copied into `out`, otherwise the power is
+    /// calculated in `out` and a scratch buffer reused between calls, so that no matrix is allocated
+    /// once both have the shape of the transition matrix (except converting a sparse transition
+    /// matrix to dense). Powers calculated here are not added to the cache.
+    pub fn transit_pow_into(&self, n: u32, out: &mut DMatrix<f32>) {
+        let size = self.map_forward.len();
+        if out.shape() != (size, size) {
+            *out = DMatrix::zeros(size, size);
+        }
+        if n == 0 {
+            out.fill_with_identity();
+            return;
+        }
+        if let Some(pow_n) = self.transit_pow_cache.lock().get(n) {
+            out.copy_from(pow_n);
+            return;
+        }
+        let transit = self.dense_transit();
+        // taken out of the cache, so that the cache is not locked while multiplying
+        let mut scratch = std::mem::replace(&mut self.transit_pow_cache.lock().scratch, DMatrix::zeros(0, 0));
+        if scratch.shape() != (size, size) {
+            scratch = DMatrix::zeros(size, size);
+        }
+        out.copy_from(transit.as_ref());
+        // square from the highest bit of `n`, which gives the same products as `transit_pow`
+        for bit in (0..n.ilog2()).rev() {
+            mat_mul_into(out, out, &mut scratch);
+            if n >> bit & 1 == 1 {
+                mat_mul_into(&scratch, transit.as_ref(), out);
+            } else {
+                std::mem::swap(out, &mut scratch);
+            }
+        }
+        self.transit_pow_cache.lock().scratch = scratch;
+    }
+
+    /// Get the nth power of transition matrix. The 0th power is the identity matrix.
+    /// 
+    /// If the nth power is cached, directly return the cached matrix, which is kept in the cache
+    /// until it becomes the least recently used power and the cache is full. Otherwise, calculate it using
+    /// binary exponent algorithm. The powers are always dense, even if the generator is sparse.
+    fn transit_pow(&self, n: u32) -> DMatrix<f32> {
+        if n == 0 {
+            let size = self.map_forward.len();
+            DMatrix::identity(size, size)
+        } else if n == 1 {
+            self.dense_transit().into_owned()
+        } else if let Some(pow_n) = self.cached_pow(n) {
+            pow_n
+        } else {
+            let pow_n_2 = self.transit_pow(n / 2);
+            let ans = if n & 1 > 0 {
+                mat_mul(&mat_mul(&pow_n_2, &pow_n_2), self.dense_transit().as_ref())
+            } else {
+                mat_mul(&pow_n_2, &pow_n_2)
+            };
+            self.transit_pow_cache.lock().insert(n, ans.clone());
+            ans
+        }
+    }
+
+    /// Get a clone of the nth power of transition matrix if it is cached. The cache is unlocked on
+    /// return, so that it can be locked again while calculating the power.
+    fn cached_pow(&self, n: u32) -> Option<DMatrix<f32>> {
+        self.transit_pow_cache.lock().get(n).cloned()
+    }
+}
+
+/// Builder of `ChordGenerator`, created by `ChordGenerator::builder`.
+///
+/// By default the generator is first order, not smoothed, not cyclic, stores the transition matrix
+/// dense and caches up to `DEFAULT_CACHE_CAPACITY` powers of it, the same as `ChordGenerator::new`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChordGeneratorBuilder {
+    order: usize,
+    smoothing: f32,
+    cyclic: bool,
+    sparse: bool,
+    cache_capacity: usize,
+}
+
+impl Default for ChordGeneratorBuilder {
+    fn default() -> Self {
+        Self { order: 1, smoothing: 0.0, cyclic: false, sparse: false, cache_capacity: DEFAULT_CACHE_CAPACITY }
+    }
+}
+
+impl ChordGeneratorBuilder {
+    /// Order of the Markov chain model, see `ChordGenerator::with_order`. Order 0 is treated as 1.
+    pub fn order(mut self, order: usize) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Additive smoothing of transitions, see `ChordGenerator::with_smoothing`.
+    pub fn smoothing(mut self, alpha: f32) -> Self {
+        self.smoothing = alpha;
+        self
+    }
+
+    /// Whether trained sequences are treated as loops, see `ChordGenerator::new_cyclic`.
+    pub fn cyclic(mut self, cyclic: bool) -> Self {
+        self.cyclic = cyclic;
+        self
+    }
+
+    /// Whether the transition matrix is stored sparse, see `ChordGenerator::new_sparse`.
+    pub fn sparse(mut self, sparse: bool) -> Self {
+        self.sparse = sparse;
+        self
+    }
+
+    /// Maximum number of cached powers of the transition matrix, see
+    /// `ChordGenerator::set_cache_capacity`.
+    pub fn cache_capacity(mut self, capacity: usize) -> Self {
+        self.cache_capacity = capacity;
+        self
+    }
+
+    /// Train a generator with the options from a sequence of chords.
+    pub fn build(self, chord_seq: &[Chord]) -> ChordGenerator {
+        let mut generator = ChordGenerator::empty(self.smoothing, self.order, self.cyclic);
+        generator.sparse = self.sparse;
+        generator.transit_pow_cache.lock().capacity = self.cache_capacity;
+        generator.train(chord_seq);
+        generator
+    }
+}
+
+/// Blend two vectors of occurrences so that the normalized result is `(1 - weight) * a + weight * b`
+/// normalized, and the total is the sum of both totals. If either vector has no occurrences, the
+/// other one is kept.
+fn blend_counts(a: &[f32], b: &[f32], weight: f32) -> Vec<f32> {
+    let (sum_a, sum_b) = (a.iter().sum::<f32>(), b.iter().sum::<f32>());
+    if sum_a > 0.0 && sum_b > 0.0 {
+        a.iter().zip(b).map(|(x, y)| ((1.0 - weight) * x / sum_a + weight * y / sum_b) * (sum_a + sum_b)).collect()
+    } else {
+        a.iter().zip(b).map(|(x, y)| x + y).collect()
+    }
+}
+
+/// Multiply two matrices. With the `rayon` feature, columns of the product are calculated in
+/// parallel.
+fn mat_mul(a: &DMatrix<f32>, b: &DMatrix<f32>) -> DMatrix<f32> {
+    let mut ans = DMatrix::zeros(a.nrows(), b.ncols());
+    mat_mul_into(a, b, &mut ans);
+    ans
+}
+
+/// Multiply two matrices into `out`, which has the shape of the product, like `mat_mul`.
+fn mat_mul_into(a: &DMatrix<f32>, b: &DMatrix<f32>, out: &mut DMatrix<f32>) {
+    #[cfg(feature = "rayon")]
+    mat_mul_par_into(a, b, out);
+    #[cfg(not(feature = "rayon"))]
+    a.mul_to(b, out);
+}
+
+/// Multiply two matrices into `out` like `mat_mul_into`, calculating the columns of the product in
+/// parallel.
+#[cfg(feature = "rayon")]
+fn mat_mul_par_into(a: &DMatrix<f32>, b: &DMatrix<f32>, out: &mut DMatrix<f32>) {
+    let nrows = out.nrows();
+    if nrows == 0 {
+        return;
+    }
+    out.as_mut_slice().par_chunks_mut(nrows).enumerate().for_each(|(j, column)| {
+        DVectorViewMut::from_slice(column, nrows).gemv(1.0, a, &b.column(j), 0.0);
+    });
+}
+
+/// Iterator of chords generated by `ChordGenerator::iter`.
+pub struct ChordIter<'a, R: Rng> {
+    generator: &'a ChordGenerator,
+    history: Vec<usize>, // index of the last `order` chords
+    options: SamplingOptions,
+    rng: R,
+}
+
+impl<R: Rng> ChordIter<'_, R> {
+    /// Generate the next chord among the chords accepted by `accept`. If no accepted chord may
+    /// follow, an accepted chord in the vocabulary is chosen uniformly, and if there is none, the
+    /// next chord is sampled among all chords.
+    pub fn next_matching(&mut self, accept: impl Fn(&Chord) -> bool) -> Option<Chord> {
+        let current = *self.history.last()?;
+        let (indices, probability) = self.generator.next_probability(&self.history);
+        let index_of = |i: usize| indices.map_or(i, |indices| indices[i]);
+        let mut probability = probability.to_vec();
+        if self.options.fifth_boost != 1.0 {
+            let root = self.generator.map_forward[current].root();
+            for (i, p) in probability.iter_mut().enumerate() {
+                if fifths_distance(root, self.generator.map_forward[index_of(i)].root()) == 1 {
+                    *p *= self.options.fifth_boost;
+                }
+            }
+        }
+        if self.options.forbid_immediate_repeat {
+            if let Some(repeat) = (0..probability.len()).position(|i| index_of(i) == current) {
+                if probability.iter().enumerate().any(|(i, &p)| i != repeat && p > 0.0) {
+                    probability[repeat] = 0.0;
+                }
+            }
+        }
+        let accepted: Vec<f32> = probability
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| {
+                if accept(&self.generator.map_forward[index_of(i)]) {
+                    p
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let gen = if accepted.iter().any(|&p| p > 0.0) {
+            let distr = WeightedIndex::new(self.options.reweight(&accepted)).ok()?;
+            index_of(self.rng.sample(distr))
+        } else if let Some(&gen) = (0..self.generator.map_forward.len())
+            .filter(|&i| accept(&self.generator.map_forward[i]))
+            .collect::<Vec<_>>()
+            .choose(&mut self.rng)
+        {
+            // no accepted chord may follow, so jump to any accepted chord in the vocabulary
+            gen
+        } else {
+            let distr = WeightedIndex::new(self.options.reweight(&probability)).ok()?;
+            index_of(self.rng.sample(distr))
+        };
+        Some(self.push(gen))
+    }
+
+    /// Append the chord with index `gen` to the history and return it.
+    fn push(&mut self, gen: usize) -> Chord {
+        self.history.push(gen);
+        if self.history.len() > self.generator.order {
+            self.history.remove(0);
+        }
+        self.generator.map_forward[gen].clone()
+    }
+}
+
+impl<R: Rng> Iterator for ChordIter<'_, R> {
+    type Item = Chord;
+
+    /// Generate the next chord. With default sampling options, the sampler cached in the generator is
+    /// used when possible instead of building one from the probabilities.
+    fn next(&mut self) -> Option<Chord> {
+        if self.options == SamplingOptions::default() {
+            if let Some((indices, distr)) = self.generator.cached_sampler(&self.history) {
+                let sampled = self.rng.sample(distr);
+                return Some(self.push(indices.map_or(sampled, |indices| indices[sampled])));
+            }
+        }
+        self.next_matching(|_| true)
+    }
+}
+
+/// Serialized form of `ChordGenerator`. The occurrences of transitions are stored row by row, from
+/// which the transition matrix is recalculated when loading.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct GeneratorData {
+    map_forward: Vec<Chord>,
+    cooccur: Vec<Vec<f32>>,
+    smoothing: f32,
+    cyclic: bool,
+    sparse: bool,
+    order: usize,
+    context_count: Vec<(Vec<usize>, Vec<f32>)>,
+    #[serde(default)]
+    backoff: Option<[f32; 3]>,
+}
+
+#[cfg(feature = "serde")]
+impl From<ChordGenerator> for GeneratorData {
+    fn from(generator: ChordGenerator) -> Self {
+        let size = generator.map_forward.len();
+        let cooccur = (0..size).map(|i| (0..size).map(|j| generator.count(i, j)).collect()).collect();
+        let context_count = generator.context_count.into_iter().collect();
+        Self {
+            map_forward: generator.map_forward,
+            cooccur,
+            smoothing: generator.smoothing,
+            cyclic: generator.cyclic,
+            sparse: generator.sparse,
+            order: generator.order,
+            context_count,
+            backoff: generator.backoff,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<GeneratorData> for ChordGenerator {
+    fn from(data: GeneratorData) -> Self {
+        let mut generator = Self::empty(data.smoothing, data.order, data.cyclic);
+        generator.sparse = data.sparse;
+        let size = data.map_forward.len();
+        generator.map_backward = data.map_forward.iter().enumerate().map(|(i, chord)| (chord.clone(), i)).collect();
+        generator.map_forward = data.map_forward;
+        let cooccur = DMatrix::from_row_iterator(size, size, data.cooccur.into_iter().flatten());
+        if generator.sparse {
+            generator.sparse_cooccur = cooccur.column_iter().map(|column| SparseColumn::from_dense(column.as_slice())).collect();
+        } else {
+            generator.cooccur = cooccur;
+        }
+        generator.context_count = data.context_count.into_iter().collect();
+        generator.backoff = data.backoff;
+        generator.update_transit();
+        generator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_init() {
         let chord_seq1 = [
             Chord::try_from("C").unwrap(),
             Chord::try_from("G").unwrap(),
             Chord::try_from("Am").unwrap(),
             Chord::try_from("F").unwrap(),
         ];
-        let cg1 = ChordGenerator::new(&chord_seq1);
+        let cg1 = ChordGenerator::new_cyclic(&chord_seq1);
         assert_eq!(cg1.map_forward, chord_seq1);
         assert_eq!(cg1.transit[(2, 1)], 1.0);
         assert_eq!(cg1.transit[(0, 3)], 1.0);
@@ -154,8 +1529,978 @@ mod tests {
             Chord::try_from("F").unwrap(),
             Chord::try_from("G").unwrap(),
         ];
-        let cg2 = ChordGenerator::new(&chord_seq2);
+        let cg2 = ChordGenerator::new_cyclic(&chord_seq2);
         assert_eq!(cg2.transit[(0, 4)], 0.5);
         assert_eq!(cg2.transit[(1, 4)], 0.5);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_init_not_cyclic() {
+        let chord_seq = [
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("G").unwrap(),
+            Chord::try_from("Am").unwrap(),
+            Chord::try_from("F").unwrap(),
+        ];
+        let cg = ChordGenerator::new(&chord_seq);
+        assert_eq!(cg.cooccur[(0, 3)], 0.0);
+        assert_eq!(cg.transit[(0, 3)], 0.25);
+        assert_eq!(cg.transit[(2, 1)], 1.0);
+    }
+
+    #[test]
+    fn test_init_terminal_chord() {
+        let chord_seq = [
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("G").unwrap(),
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("G").unwrap(),
+            Chord::try_from("F").unwrap(),
+        ];
+        let cg = ChordGenerator::new(&chord_seq);
+        assert!(cg.transit.iter().all(|p| p.is_finite()));
+        for i in 0..cg.map_forward.len() {
+            assert!((cg.transit.column(i).sum() - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_init_unknown_chord() {
+        let chord_seq: Vec<Chord> = ["C", "G", "Am", "F"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let cg = ChordGenerator::new(&chord_seq);
+        let e = Chord::try_from("E").unwrap();
+        assert_eq!(cg.generate_seeded(e.clone(), 4, 0), Err(Error::ChordNotInCorpus(e.clone())));
+        let options = SamplingOptions { cadence: Some(Key::major(3)), ..Default::default() };
+        assert_eq!(cg.generate_with_options(e.clone(), 4, options, &mut StdRng::seed_from_u64(0)), Err(Error::ChordNotInCorpus(e.clone())));
+        assert_eq!(cg.iter(e, StdRng::seed_from_u64(0)).next(), None);
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let chord_seq = [
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("G").unwrap(),
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("Am").unwrap(),
+        ];
+        let cg = ChordGenerator::new(&chord_seq);
+        let mut buf = Vec::new();
+        cg.to_csv(&mut buf).unwrap();
+        let csv = String::from_utf8(buf).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "from\\to,C,G,Am");
+        assert_eq!(lines[1], "C,0,0.5,0.5");
+        assert_eq!(lines[2], "G,1,0,0");
+    }
+
+    #[test]
+    fn test_display_matrix() {
+        let chord_seq: Vec<Chord> = ["C", "G", "C", "Am7"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let cg = ChordGenerator::new(&chord_seq);
+        let table = cg.display_matrix();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0].split_whitespace().collect::<Vec<_>>(), ["from\\to", "C", "G", "Am7"]);
+        assert_eq!(lines[0], "from\\to    C    G  Am7");
+        assert_eq!(lines[1], "C       0.00 0.50 0.50");
+        assert_eq!(lines[3], "Am7     0.33 0.33 0.33");
+    }
+
+    #[test]
+    fn test_log_likelihood() {
+        let chord_seq: Vec<Chord> = ["C", "G", "Am", "F", "C", "G", "F", "C"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let cg = ChordGenerator::new(&chord_seq);
+        let shuffled: Vec<Chord> = ["C", "F", "Am", "G", "C", "F", "G", "C"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let trained = cg.log_likelihood(&chord_seq).unwrap();
+        assert!(trained.is_finite());
+        assert!(trained > cg.log_likelihood(&shuffled).unwrap());
+        assert!((cg.log_likelihood(&chord_seq[..3]).unwrap() - 0.5f64.ln()).abs() < 1e-6);
+        assert_eq!(cg.log_likelihood(&chord_seq[..1]).unwrap(), 0.0);
+        let unknown = Chord::try_from("Dm").unwrap();
+        assert_eq!(cg.log_likelihood(std::slice::from_ref(&unknown)).unwrap_err(), Error::ChordNotInCorpus(unknown));
+    }
+
+    #[test]
+    fn test_iter() {
+        let chord_seq: Vec<Chord> = ["C", "G", "Am", "F", "C", "G", "F", "C"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let cg = ChordGenerator::new(&chord_seq);
+        let generated = cg.generate_seeded(chord_seq[0].clone(), 4, 5).unwrap();
+        let iterated: Vec<Chord> = cg.iter(chord_seq[0].clone(), StdRng::seed_from_u64(5)).take(4).collect();
+        assert_eq!(generated, iterated);
+        let mut rng = StdRng::seed_from_u64(5);
+        assert_eq!(cg.iter(chord_seq[0].clone(), &mut rng).take(100).count(), 100);
+
+        let cg2 = ChordGenerator::with_order(&chord_seq, 2);
+        let generated = cg2.generate_seeded(chord_seq[0].clone(), 16, 5).unwrap();
+        let iterated: Vec<Chord> = cg2.iter(chord_seq[0].clone(), StdRng::seed_from_u64(5)).take(16).collect();
+        assert_eq!(generated, iterated);
+    }
+
+    #[test]
+    fn test_new_sparse() {
+        // 300 chords, each followed by only 2 chords
+        let chords: Vec<Chord> = (0..12u8)
+            .flat_map(|root| {
+                [
+                    "", "m", "7", "M7", "m7", "dim", "aug", "sus2", "sus4", "6", "m6", "9", "add9",
+                    "ø7", "13", "m9", "M9", "11", "m11", "dim7", "aug7", "m13", "M13", "M11",
+                    "madd9",
+                ]
+                .into_iter()
+                .map(move |quality| {
+                    Chord::try_from(
+                        format!("{}{}", crate::note::note_string(root)[0], quality).as_str(),
+                    )
+                    .unwrap()
+                })
+            })
+            .collect();
+        assert_eq!(chords.len(), 300);
+        let chord_seq: Vec<Chord> = chords.iter().chain(chords.iter().step_by(2)).cloned().collect();
+        let dense = ChordGenerator::new(&chord_seq);
+        let sparse = ChordGenerator::new_sparse(&chord_seq);
+        assert!(sparse.is_sparse());
+        assert_eq!(sparse.transit.len(), 0);
+        assert_eq!(sparse.cooccur.len(), 0);
+        assert!(sparse.sparse_cooccur.iter().map(|column| column.weights.len()).sum::<usize>() < 1000);
+        assert!(sparse.sparse_transit.iter().map(|column| column.weights.len()).sum::<usize>() < 1000);
+        assert_eq!(sparse.dense_transit().as_ref(), &dense.transit);
+        for seed in 0..4 {
+            assert_eq!(
+                sparse.generate_seeded(chords[0].clone(), 32, seed).unwrap(),
+                dense.generate_seeded(chords[0].clone(), 32, seed).unwrap()
+            );
+        }
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(sparse.generate_range(chords[0].clone(), chords[4].clone(), 5, &mut rng).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_probability_on_shared() {
+        let chord_seq: Vec<Chord> = ["C", "G", "Am", "F", "C", "Am", "G", "F", "C"]
+            .into_iter()
+            .map(|s| Chord::try_from(s).unwrap())
+            .collect();
+        let cg = ChordGenerator::new(&chord_seq);
+        let (c, f) = (chord_seq[0].clone(), chord_seq[3].clone());
+        let shared = &cg;
+        let expected = shared.probability_on(c.clone(), f.clone(), 6, 3).unwrap();
+        assert!((expected.sum() - 1.0).abs() < 1e-5);
+        assert!(!cg.transit_pow_cache.lock().is_empty());
+        // the generator is shared between threads, which all use the same cache
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|i| {
+                    let (c, f) = (c.clone(), f.clone());
+                    scope.spawn(move || {
+                        (
+                            shared.probability_on(c.clone(), f.clone(), 6, 3).unwrap(),
+                            shared
+                                .generate_range(c, f, 6 + i, &mut StdRng::seed_from_u64(i as u64))
+                                .unwrap(),
+                        )
+                    })
+                })
+                .collect();
+            for (i, handle) in handles.into_iter().enumerate() {
+                let (probability, range) = handle.join().unwrap();
+                assert_eq!(probability, expected);
+                assert_eq!(range.len(), 4 + i);
+            }
+        });
+    }
+
+    #[test]
+    fn test_generate_range_small() {
+        let chord_seq: Vec<Chord> = ["C", "F", "G", "C"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let cg = ChordGenerator::new(&chord_seq);
+        let mut rng = StdRng::seed_from_u64(0);
+        let (c, g) = (chord_seq[0].clone(), chord_seq[2].clone());
+        assert_eq!(cg.generate_range(c.clone(), g.clone(), 0, &mut rng).unwrap_err(), Error::InvalidRange(0));
+        assert_eq!(cg.generate_range(c.clone(), g.clone(), 1, &mut rng).unwrap_err(), Error::InvalidRange(1));
+        assert_eq!(cg.generate_range(c.clone(), g.clone(), 2, &mut rng).unwrap(), vec![]);
+        let am = Chord::try_from("Am").unwrap();
+        assert_eq!(cg.generate_range(am.clone(), g.clone(), 2, &mut rng).unwrap_err(), Error::ChordNotInCorpus(am.clone()));
+        assert_eq!(cg.generate_range(c, am.clone(), 5, &mut rng).unwrap_err(), Error::ChordNotInCorpus(am));
+    }
+
+    #[test]
+    fn test_generate_range_candidates() {
+        let chord_seq: Vec<Chord> = ["C", "G", "Am", "F", "C", "Am", "G", "F", "C"]
+            .into_iter()
+            .map(|s| Chord::try_from(s).unwrap())
+            .collect();
+        let cg = ChordGenerator::new(&chord_seq);
+        let mut rng = StdRng::seed_from_u64(0);
+        let (c, f) = (chord_seq[0].clone(), chord_seq[3].clone());
+        let candidates = cg.generate_range_candidates(c.clone(), f.clone(), 6, 4, &mut rng).unwrap();
+        assert!(candidates.len() > 1 && candidates.len() <= 4);
+        for (i, candidate) in candidates.iter().enumerate() {
+            assert_eq!(candidate.len(), 4);
+            assert!(!candidates[..i].contains(candidate));
+        }
+        // C is always followed by G or Am, which are only followed by each other or F
+        let only = cg.generate_range_candidates(c.clone(), f.clone(), 3, 4, &mut rng).unwrap();
+        assert_eq!(only.len(), 2);
+        assert_eq!(cg.generate_range_candidates(c, f, 1, 4, &mut rng).unwrap_err(), Error::InvalidRange(1));
+    }
+
+    #[test]
+    fn test_best_range() {
+        let chord_seq: Vec<Chord> = ["C", "Am", "F", "G", "C", "Am", "Dm", "G", "E"]
+            .into_iter()
+            .map(|s| Chord::try_from(s).unwrap())
+            .collect();
+        let cg = ChordGenerator::new(&chord_seq);
+        let [c, am, f, g, dm, e] = ["C", "Am", "F", "G", "Dm", "E"].map(|s| Chord::try_from(s).unwrap());
+        // F and Dm are equally likely after Am, and the other transitions are forced
+        assert_eq!(cg.best_range(c.clone(), c.clone(), 5, 1), Ok(vec![am.clone(), f.clone(), g.clone()]));
+        assert_eq!(cg.best_range(c.clone(), e.clone(), 6, 3), Ok(vec![am.clone(), f.clone(), g.clone(), e.clone()]));
+        assert_eq!(cg.best_range(am.clone(), g.clone(), 3, 3), Ok(vec![f.clone()]));
+        assert_eq!(cg.best_range(dm.clone(), g.clone(), 2, 3), Ok(vec![]));
+        // E is never followed by any chord, so every chord may follow with uniform probability, and a
+        // beam too narrow keeps only C, which is never followed by C
+        assert_eq!(cg.best_range(e.clone(), c.clone(), 3, 6), Ok(vec![g.clone()]));
+        assert_eq!(cg.best_range(e.clone(), c.clone(), 3, 1), Err(Error::EmptyCorpus));
+        assert_eq!(cg.best_range(c.clone(), g.clone(), 3, 3), Err(Error::EmptyCorpus));
+        assert_eq!(cg.best_range(c.clone(), g.clone(), 1, 3), Err(Error::InvalidRange(1)));
+    }
+
+    #[test]
+    fn test_generate_bounded() {
+        let chord_seq: Vec<Chord> = [
+            "C", "F", "G7", "C", "Am", "Dm", "G7", "C", "Em", "F", "G", "Am",
+        ]
+        .into_iter()
+        .map(|s| Chord::try_from(s).unwrap())
+        .collect();
+        let c = chord_seq[0].clone();
+        let cg = ChordGenerator::new_cyclic(&chord_seq);
+        let options = SamplingOptions { cadence: Some(Key::major(3)), ..Default::default() };
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut lengths = HashSet::new();
+        for _ in 0..100 {
+            let generated = cg.generate_bounded(c.clone(), 3, 8, options, &mut rng).unwrap();
+            assert!((3..=8).contains(&generated.len()));
+            if generated.len() < 8 {
+                let n = generated.len();
+                assert!(generated[n - 2].to_string() == "G7" && generated[n - 1] == c);
+            }
+            lengths.insert(generated.len());
+        }
+        assert!(lengths.len() > 1);
+        // without a cadence and absorbing states, the maximum length is always reached
+        assert_eq!(cg.generate_bounded(c.clone(), 3, 8, SamplingOptions::default(), &mut rng).unwrap().len(), 8);
+        // the generation stops at the absorbing state as soon as allowed
+        let absorbing = ChordGenerator::new(&["C", "G", "F", "F"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect::<Vec<_>>());
+        assert_eq!(absorbing.generate_bounded(c.clone(), 4, 6, SamplingOptions::default(), &mut rng).unwrap().len(), 4);
+        assert_eq!(cg.generate_bounded(c, 5, 4, options, &mut rng), Err(Error::InvalidLengthBounds(5, 4)));
+    }
+
+    #[test]
+    fn test_generate_batch() {
+        let chord_seq: Vec<Chord> = [
+            "C", "G", "Am", "F", "C", "Am", "Dm", "G", "C", "F", "G", "C",
+        ]
+        .into_iter()
+        .map(|s| Chord::try_from(s).unwrap())
+        .collect();
+        let cg = ChordGenerator::new(&chord_seq);
+        let batch = cg.generate_batch(chord_seq[0].clone(), 6, 8, &mut StdRng::seed_from_u64(0)).unwrap();
+        assert_eq!(batch.len(), 8);
+        assert!(batch.iter().all(|seq| seq.len() == 6));
+        assert!(batch.iter().any(|seq| seq != &batch[0]));
+        assert_eq!(cg.generate_batch(chord_seq[0].clone(), 6, 0, &mut StdRng::seed_from_u64(0)), Ok(vec![]));
+        let e = Chord::try_from("E").unwrap();
+        assert_eq!(cg.generate_batch(e.clone(), 6, 8, &mut StdRng::seed_from_u64(0)), Err(Error::ChordNotInCorpus(e)));
+    }
+
+    #[test]
+    fn test_cached_sampler() {
+        let chord_seq: Vec<Chord> = [
+            "C", "G", "Am", "F", "C", "Am", "Dm", "G", "C", "F", "G", "C",
+        ]
+        .into_iter()
+        .map(|s| Chord::try_from(s).unwrap())
+        .collect();
+        let c = chord_seq[0].clone();
+        let mut observed = ChordGenerator::new(&chord_seq);
+        observed.observe(c.clone(), Chord::try_from("Em").unwrap());
+        let generators = [
+            ChordGenerator::new(&chord_seq),
+            ChordGenerator::new_sparse(&chord_seq),
+            ChordGenerator::with_order(&chord_seq, 2),
+            ChordGenerator::with_smoothing(&chord_seq, 0.5),
+            observed,
+        ];
+        for cg in generators {
+            let cached = cg.generate_seeded(c.clone(), 200, 7).unwrap();
+            let mut iter = cg.iter(c.clone(), StdRng::seed_from_u64(7));
+            let fresh: Vec<Chord> = (0..200).map_while(|_| iter.next_matching(|_| true)).collect();
+            assert_eq!(cached, fresh);
+        }
+    }
+
+    #[test]
+    fn test_absorbing_states() {
+        let chord_seq: Vec<Chord> = ["C", "G", "Am", "F", "F", "F"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let cg = ChordGenerator::new(&chord_seq);
+        assert_eq!(cg.absorbing_states(), [chord_seq[3].clone()]);
+        assert_eq!(ChordGenerator::builder().sparse(true).build(&chord_seq).absorbing_states(), [chord_seq[3].clone()]);
+        // smoothing gives every chord a way out
+        assert_eq!(ChordGenerator::with_smoothing(&chord_seq, 0.5).absorbing_states(), []);
+        // a chord never followed by any chord moves to a random chord instead
+        assert_eq!(ChordGenerator::new(&chord_seq[..4]).absorbing_states(), []);
+    }
+
+    #[test]
+    fn test_degree_stats() {
+        let chord_seq: Vec<Chord> = ["C", "G", "Am", "C", "F", "G", "Em"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let stats = ChordGenerator::new(&chord_seq).degree_stats();
+        let expected = [("C", 1, 2), ("G", 2, 2), ("Am", 1, 1), ("F", 1, 1), ("Em", 1, 0)];
+        assert_eq!(stats.len(), expected.len());
+        for (name, in_degree, out_degree) in expected {
+            assert!(stats.contains(&(Chord::try_from(name).unwrap(), in_degree, out_degree)), "{name}");
+        }
+        // smoothing does not add edges
+        assert_eq!(ChordGenerator::with_smoothing(&chord_seq, 0.5).degree_stats(), stats);
+    }
+
+    #[test]
+    fn test_new_decayed() {
+        let chord_seq: Vec<Chord> = ["C", "G", "C", "G", "C", "F"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let (c, g, f) = (chord_seq[0].clone(), chord_seq[1].clone(), chord_seq[5].clone());
+        // C is followed by G twice, but the last transition from C to F dominates
+        let cg = ChordGenerator::new_decayed(&chord_seq, 0.1);
+        assert!((cg.transition_probability(c.clone(), f.clone()).unwrap() - 1.0 / 1.0101).abs() < 1e-6);
+        assert_eq!(cg.most_likely_next(c.clone()), Ok(f.clone()));
+        assert!((cg.cooccur.column(0).sum() - 1.0101).abs() < 1e-6);
+        // without decay, every transition has weight 1
+        let undecayed = ChordGenerator::new_decayed(&chord_seq, 1.0);
+        assert_eq!(undecayed.cooccur, ChordGenerator::new(&chord_seq).cooccur);
+        assert_eq!(undecayed.most_likely_next(c.clone()), Ok(g));
+        assert_eq!(ChordGenerator::new_decayed(&chord_seq, 0.0).transition_probability(c, f), Ok(1.0));
+    }
+
+    #[test]
+    fn test_vocabulary() {
+        let chord_seq: Vec<Chord> = ["C", "G", "Am", "C", "F"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let cg = ChordGenerator::new(&chord_seq);
+        assert_eq!(cg.vocabulary(), [chord_seq[0].clone(), chord_seq[1].clone(), chord_seq[2].clone(), chord_seq[4].clone()]);
+        assert!(chord_seq.iter().all(|chord| cg.contains(chord)));
+        assert!(!cg.contains(&Chord::try_from("Dm").unwrap()));
+        assert!(!cg.contains(&Chord::try_from("C7").unwrap()));
+        assert!(ChordGenerator::new(&[]).vocabulary().is_empty());
+    }
+
+    #[test]
+    fn test_builder() {
+        let chord_seq: Vec<Chord> = ["C", "F", "G", "C", "Am", "F", "G", "C"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let cg = ChordGenerator::builder().order(2).smoothing(0.5).build(&chord_seq);
+        assert_eq!(cg.order(), 2);
+        assert_eq!(cg.smoothing, 0.5);
+        assert!(!cg.cyclic && !cg.is_sparse());
+        assert!(cg.transit.iter().all(|&p| p > 0.0));
+        assert_eq!(cg.context_count.len(), ChordGenerator::with_order(&chord_seq, 2).context_count.len());
+
+        let default = ChordGenerator::builder().build(&chord_seq);
+        assert_eq!(default.transit, ChordGenerator::new(&chord_seq).transit);
+        let cyclic = ChordGenerator::builder().cyclic(true).sparse(true).build(&chord_seq);
+        assert!(cyclic.cyclic && cyclic.is_sparse());
+    }
+
+    #[test]
+    fn test_enharmonic_vocabulary() {
+        let chord_seq: Vec<Chord> = ["C#", "F#", "Db", "Gb", "C#"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let cg = ChordGenerator::new(&chord_seq);
+        assert_eq!(cg.map_forward.len(), 2);
+        let mut rng = StdRng::seed_from_u64(0);
+        let generated = cg.generate(Chord::try_from("Db").unwrap(), 4, &mut rng).unwrap();
+        assert_eq!(generated, ["F#", "C#", "F#", "C#"].map(|s| Chord::try_from(s).unwrap()));
+    }
+
+    #[test]
+    fn test_transition_probability() {
+        let chord_seq: Vec<Chord> = ["C", "G", "C", "F", "C", "G", "C", "Am"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let [c, g, f, am] = ["C", "G", "F", "Am"].map(|s| Chord::try_from(s).unwrap());
+        for cg in [ChordGenerator::new(&chord_seq), ChordGenerator::new_sparse(&chord_seq)] {
+            assert_eq!(cg.transition_probability(c.clone(), g.clone()), Ok(0.5));
+            assert_eq!(cg.transition_probability(c.clone(), am.clone()), Ok(0.25));
+            assert_eq!(cg.transition_probability(g.clone(), c.clone()), Ok(1.0));
+            assert_eq!(cg.transition_probability(f.clone(), g.clone()), Ok(0.0));
+            assert_eq!(cg.transition_probability(c.clone(), c.clone()), Ok(0.0));
+            let dm = Chord::try_from("Dm").unwrap();
+            assert_eq!(cg.transition_probability(dm.clone(), c.clone()), Err(Error::ChordNotInCorpus(dm.clone())));
+            assert_eq!(cg.transition_probability(c.clone(), dm.clone()), Err(Error::ChordNotInCorpus(dm)));
+        }
+    }
+
+    #[test]
+    fn test_most_likely_next() {
+        // C is followed by G twice, and by F and Am once
+        let chord_seq: Vec<Chord> = [
+            "C", "G", "C", "F", "C", "G", "C", "Am", "Em", "F", "Em", "Am",
+        ]
+        .into_iter()
+        .map(|s| Chord::try_from(s).unwrap())
+        .collect();
+        let [c, g, f, em, am] = ["C", "G", "F", "Em", "Am"].map(|s| Chord::try_from(s).unwrap());
+        for cg in [ChordGenerator::new(&chord_seq), ChordGenerator::new_sparse(&chord_seq)] {
+            assert_eq!(cg.most_likely_next(c.clone()), Ok(g.clone()));
+            assert_eq!(cg.most_likely_next(g.clone()), Ok(c.clone()));
+            // F is followed by C and Em once each, and Em by F and Am once each
+            assert_eq!(cg.most_likely_next(f.clone()), Ok(c.clone()));
+            assert_eq!(cg.most_likely_next(em.clone()), Ok(am.clone()));
+            let dm = Chord::try_from("Dm").unwrap();
+            assert_eq!(cg.most_likely_next(dm.clone()), Err(Error::ChordNotInCorpus(dm)));
+        }
+    }
+
+    #[test]
+    fn test_top_k_next() {
+        // C is followed by G twice, and by F and Am once
+        let chord_seq: Vec<Chord> = ["C", "G", "C", "F", "C", "G", "C", "Am"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let c = chord_seq[0].clone();
+        for cg in [ChordGenerator::new(&chord_seq), ChordGenerator::new_sparse(&chord_seq)] {
+            let expected: Vec<(Chord, f32)> = [("G", 0.5), ("F", 0.25), ("Am", 0.25)]
+                .into_iter()
+                .map(|(s, p)| (Chord::try_from(s).unwrap(), p))
+                .collect();
+            assert_eq!(cg.top_k_next(c.clone(), 2).unwrap(), expected[..2]);
+            assert_eq!(cg.top_k_next(c.clone(), 10).unwrap(), expected);
+            assert_eq!(cg.top_k_next(c.clone(), 0).unwrap(), vec![]);
+            let dm = Chord::try_from("Dm").unwrap();
+            assert_eq!(cg.top_k_next(dm.clone(), 2).unwrap_err(), Error::ChordNotInCorpus(dm));
+        }
+    }
+
+    #[test]
+    fn test_generate_in_key() {
+        let chord_seq: Vec<Chord> = ["C", "Eb", "C", "Eb", "C", "F", "Db", "C", "F", "G7", "C"]
+            .into_iter()
+            .map(|s| Chord::try_from(s).unwrap())
+            .collect();
+        let cg = ChordGenerator::new(&chord_seq);
+        let key = Key::major(3);
+        let scale = key.scale();
+        let mut rng = StdRng::seed_from_u64(0);
+        let generated = cg.generate_in_key(chord_seq[0].clone(), 100, key, &mut rng).unwrap();
+        assert_eq!(generated.len(), 100);
+        assert!(generated.iter().all(|chord| chord.notes().iter().all(|note| scale.contains(note))));
+        // only Db is diatonic in Eb minor, which only follows F, so other chords fall back to the
+        // unfiltered distribution
+        let generated = cg.generate_in_key(chord_seq[0].clone(), 100, Key::minor(6), &mut rng).unwrap();
+        assert_eq!(generated.len(), 100);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let chord_seq: Vec<Chord> = ["C", "G", "C"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let cg = ChordGenerator::new(&chord_seq);
+        let dot = cg.to_dot(0.0);
+        assert_eq!(
+            dot,
+            "digraph {\n    0 [label=\"C\"];\n    1 [label=\"G\"];\n    \
+             0 -> 1 [label=\"1.00\", weight=1];\n    1 -> 0 [label=\"1.00\", weight=1];\n}\n"
+        );
+        assert_eq!(dot.matches("->").count(), 2);
+
+        let chord_seq: Vec<Chord> = ["C", "G", "C", "Am", "C", "G"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let cg = ChordGenerator::with_smoothing(&chord_seq, 0.1);
+        assert_eq!(cg.to_dot(0.0).matches("->").count(), 9);
+        assert_eq!(cg.to_dot(0.5).matches("->").count(), 3);
+    }
+
+    #[test]
+    fn test_stationary_distribution() {
+        // C is followed by C three times and by G once, and G is always followed by C
+        let chord_seq: Vec<Chord> = ["C", "C", "C", "C", "G", "C"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let cg = ChordGenerator::new(&chord_seq);
+        let stationary = cg.stationary_distribution();
+        assert!((stationary[0] - 0.8).abs() < 1e-4);
+        assert!((stationary[1] - 0.2).abs() < 1e-4);
+        assert!((cg.transit.clone() * &stationary - &stationary).abs().max() < 1e-4);
+
+        // periodic chain
+        let chord_seq: Vec<Chord> = ["C", "G", "C", "G"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let stationary = ChordGenerator::new_sparse(&chord_seq).stationary_distribution();
+        assert!((stationary[0] - 0.5).abs() < 1e-4);
+        assert!((stationary[1] - 0.5).abs() < 1e-4);
+        assert_eq!(ChordGenerator::new(&[]).stationary_distribution().len(), 0);
+    }
+
+    #[test]
+    fn test_entropy() {
+        let chord_seq: Vec<Chord> = ["C", "G", "Am", "C", "G", "Am"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        assert_eq!(ChordGenerator::new_cyclic(&chord_seq).entropy(), 0.0);
+
+        // no transitions are trained, so every transition is equally likely
+        let mut cg = ChordGenerator::new(&[]);
+        for chord in &chord_seq[..3] {
+            cg.train(std::slice::from_ref(chord));
+        }
+        assert!((cg.entropy() - 3f32.log2()).abs() < 1e-5);
+
+        // C is followed by G or Am with equal probability, and the others are deterministic
+        let chord_seq: Vec<Chord> = ["C", "G", "C", "Am", "C"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        assert!((ChordGenerator::new(&chord_seq).entropy() - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_transit_pow_0() {
+        let chord_seq: Vec<Chord> = ["C", "G", "Am", "F", "C"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let cg = ChordGenerator::new(&chord_seq);
+        assert_eq!(cg.transit_pow(0), DMatrix::identity(4, 4));
+        let mut out = DMatrix::zeros(0, 0);
+        cg.transit_pow_into(0, &mut out);
+        assert_eq!(out, DMatrix::identity(4, 4));
+        // the chord at index 0 is always the left chord
+        let probability = cg.probability_on(chord_seq[0].clone(), chord_seq[4].clone(), 4, 0).unwrap();
+        assert_eq!(probability, DVector::from_vec(vec![1.0, 0.0, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn test_pow_cache() {
+        let mut cache = PowCache::new(3);
+        for n in 1..=3 {
+            cache.insert(n, DMatrix::from_element(1, 1, n as f32));
+        }
+        assert!(cache.get(1).is_some());
+        cache.insert(4, DMatrix::from_element(1, 1, 4.0));
+        // 2 is the least recently used
+        assert_eq!(cache.len(), 3);
+        assert!(cache.get(2).is_none());
+        assert_eq!(cache.get(1), Some(&DMatrix::from_element(1, 1, 1.0)));
+        cache.capacity = 1;
+        cache.shrink();
+        assert_eq!(cache.powers.keys().collect::<Vec<_>>(), [&1]);
+        cache.clear();
+        assert!(cache.is_empty() && cache.recent.is_empty());
+    }
+
+    #[test]
+    fn test_cache_capacity() {
+        let chord_seq: Vec<Chord> = ["C", "G", "Am", "F", "C", "F", "G", "C"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let unbounded = ChordGenerator::builder().cache_capacity(usize::MAX).build(&chord_seq);
+        let mut cg = ChordGenerator::builder().cache_capacity(3).build(&chord_seq);
+        for n in 2..40 {
+            assert!((cg.transit_pow(n) - unbounded.transit_pow(n)).abs().max() < 1e-6);
+            assert!(cg.transit_pow_cache.lock().len() <= 3);
+        }
+        assert!(unbounded.transit_pow_cache.lock().len() > 3);
+        cg.set_cache_capacity(1);
+        assert_eq!(cg.transit_pow_cache.lock().len(), 1);
+        cg.set_cache_capacity(0);
+        cg.transit_pow(8);
+        assert!(cg.transit_pow_cache.lock().is_empty());
+        let mut out = DMatrix::zeros(0, 0);
+        cg.transit_pow_into(8, &mut out);
+        assert!((out - unbounded.transit_pow(8)).abs().max() < 1e-6);
+        assert_eq!(ChordGenerator::new(&chord_seq).transit_pow_cache.lock().capacity, DEFAULT_CACHE_CAPACITY);
+    }
+
+    #[test]
+    fn test_merge() {
+        let [c, g, f] = ["C", "G", "F"].map(|s| Chord::try_from(s).unwrap());
+        // C is always followed by G in the first model, and by F in the second model
+        let first = ChordGenerator::new(&[c.clone(), g.clone()]);
+        let second = ChordGenerator::new(&[c.clone(), f.clone(), c.clone(), f.clone(), c.clone()]);
+        let merged = first.merge(&second, 0.25);
+        assert_eq!(merged.map_forward, [c.clone(), g.clone(), f.clone()]);
+        assert!((merged.transition_probability(c.clone(), g.clone()).unwrap() - 0.75).abs() < 1e-6);
+        assert!((merged.transition_probability(c.clone(), f.clone()).unwrap() - 0.25).abs() < 1e-6);
+        // F only appears in the second model
+        assert_eq!(merged.transition_probability(f.clone(), c.clone()), Ok(1.0));
+        // G is never followed by any chord
+        assert!((merged.transition_probability(g.clone(), c.clone()).unwrap() - 1.0 / 3.0).abs() < 1e-6);
+        // occurrences of C are 1 + 2
+        assert!((merged.cooccur.column(0).sum() - 3.0).abs() < 1e-6);
+
+        let merged = second.merge(&first, 1.0);
+        assert_eq!(merged.map_forward, [c.clone(), f.clone(), g.clone()]);
+        assert_eq!(merged.transition_probability(c.clone(), g.clone()), Ok(1.0));
+
+        // contexts of higher order are blended in the same way
+        let first = ChordGenerator::with_order(&[c.clone(), g.clone(), c.clone()], 2);
+        let second = ChordGenerator::with_order(&[c.clone(), g.clone(), f.clone()], 2);
+        let merged = first.merge(&second, 0.5);
+        assert_eq!(merged.context_count[&vec![0, 1]], [1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_blend_generate() {
+        let [c, g, am, f, e] = ["C", "G", "Am", "F", "E"].map(|s| Chord::try_from(s).unwrap());
+        // both models are deterministic loops, and E only appears in the second model
+        let first = ChordGenerator::new_cyclic(&[c.clone(), g.clone(), am.clone(), f.clone()]);
+        let second = ChordGenerator::new_cyclic(&[c.clone(), f.clone(), am.clone(), g.clone()]);
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(first.blend_generate(&second, 0.0, c.clone(), 8, &mut rng), first.generate(c.clone(), 8, &mut rng));
+        assert_eq!(first.blend_generate(&second, 1.0, c.clone(), 8, &mut rng), second.generate(c.clone(), 8, &mut rng));
+        let seq = first.blend_generate(&second, 0.5, c.clone(), 16, &mut rng).unwrap();
+        assert!(seq.iter().all(|chord| [&c, &g, &am, &f].contains(&chord)));
+
+        let third = ChordGenerator::new(&[c.clone(), e.clone()]);
+        assert_eq!(first.blend_generate(&third, 0.5, e.clone(), 1, &mut rng), Err(Error::ChordNotInCorpus(e.clone())));
+        // C is only followed by E in the third model, which is not in the first model
+        assert_eq!(first.blend_generate(&third, 1.0, c.clone(), 1, &mut rng), Err(Error::EmptyCorpus));
+    }
+
+    #[test]
+    fn test_with_backoff() {
+        let chord_seq: Vec<Chord> = ["C", "G", "Am", "F", "C", "G", "F", "C"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let cg = ChordGenerator::with_backoff(&chord_seq, [0.6, 0.3, 0.1]);
+        let [c, g, am, f] = [0, 1, 2, 3];
+        // the unigram distribution counts the chords following any chord: G, Am, F, C, G, F, C
+        assert_eq!(cg.unigram, [2.0 / 7.0, 2.0 / 7.0, 1.0 / 7.0, 2.0 / 7.0]);
+        // G is followed by Am after C, G, and by Am or F after G
+        let (_, probability) = cg.next_probability(&[c, g]);
+        let expected = [0.1 * 2.0 / 7.0, 0.1 * 2.0 / 7.0, 0.6 * 0.5 + 0.3 * 0.5 + 0.1 / 7.0, 0.6 * 0.5 + 0.3 * 0.5 + 0.1 * 2.0 / 7.0];
+        assert!(probability.iter().zip(expected).all(|(p, e)| (p - e).abs() < 1e-6));
+        // F, Am never appears, so only order 1 and unigram distributions are interpolated
+        let (_, probability) = cg.next_probability(&[f, am]);
+        assert!((probability.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+        assert!((probability[f] - (0.75 + 0.25 * 2.0 / 7.0)).abs() < 1e-6);
+        assert!(probability.iter().all(|&p| p > 0.0));
+        assert_eq!(cg.generate_seeded(chord_seq[0].clone(), 16, 0).unwrap().len(), 16);
+    }
+
+    #[test]
+    fn test_observe() {
+        let chord_seq: Vec<Chord> = ["C", "G", "C", "F", "C"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let am = Chord::try_from("Am").unwrap();
+        let mut cg = ChordGenerator::new(&chord_seq);
+        cg.transit_pow(2);
+        cg.observe(chord_seq[0].clone(), am.clone());
+        assert_eq!(cg.map_forward.len(), 4);
+        assert!(cg.transit_pow_cache.lock().is_empty());
+        assert!((cg.transition_probability(chord_seq[0].clone(), am.clone()).unwrap() - 1.0 / 3.0).abs() < 1e-6);
+        let mut last = 1.0 / 3.0;
+        for _ in 0..100 {
+            cg.observe(chord_seq[0].clone(), am.clone());
+            let p = cg.transition_probability(chord_seq[0].clone(), am.clone()).unwrap();
+            assert!(p > last);
+            last = p;
+        }
+        assert!(last > 0.98);
+
+        // same as training on the sequence
+        let mut observed = ChordGenerator::new_sparse(&[]);
+        for window in chord_seq.windows(2) {
+            observed.observe(window[0].clone(), window[1].clone());
+        }
+        assert_eq!(observed.dense_transit().into_owned(), ChordGenerator::new(&chord_seq).transit);
+
+        // observing known chords only updates the column of the first chord and the unigram
+        let mut trained = ChordGenerator::new(&chord_seq);
+        trained.train(&[chord_seq[1].clone(), chord_seq[3].clone()]);
+        let mut observed = ChordGenerator::new(&chord_seq);
+        let samplers_before = observed.samplers.clone();
+        observed.observe(chord_seq[1].clone(), chord_seq[3].clone());
+        assert_eq!(observed.transit, trained.transit);
+        assert!(observed.unigram.iter().zip(&trained.unigram).all(|(p, q)| (p - q).abs() < 1e-6));
+        assert_eq!(observed.total, trained.total);
+        for (i, (before, after)) in samplers_before.iter().zip(&observed.samplers).enumerate() {
+            assert_eq!(before == after, i != 1);
+        }
+    }
+
+    #[test]
+    fn test_mat_mul() {
+        let a = DMatrix::from_fn(7, 5, |i, j| (i * 5 + j) as f32 / 10.0);
+        let b = DMatrix::from_fn(5, 3, |i, j| (i + 2 * j) as f32 / 7.0);
+        let product = mat_mul(&a, &b);
+        assert_eq!(product.shape(), (7, 3));
+        assert!((product - &a * &b).iter().all(|d| d.abs() < 1e-5));
+        assert_eq!(mat_mul(&DMatrix::zeros(0, 0), &DMatrix::zeros(0, 0)).shape(), (0, 0));
+    }
+
+    #[test]
+    fn test_transit_pow_into() {
+        let chord_seq: Vec<Chord> = ["C", "G", "Am", "F", "C", "G", "F", "C"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let cg = ChordGenerator::new(&chord_seq);
+        let mut out = DMatrix::zeros(0, 0);
+        cg.transit_pow_into(1, &mut out);
+        assert_eq!(out, cg.transit);
+        cg.transit_pow_into(5, &mut out);
+        let expected = &cg.transit * &cg.transit * &cg.transit * &cg.transit * &cg.transit;
+        assert!((&out - &expected).iter().all(|d| d.abs() < 1e-5));
+        assert_eq!(out, cg.transit_pow(5));
+        let mut uncached = cg.clone();
+        uncached.set_cache_capacity(0);
+        uncached.transit_pow_into(6, &mut out);
+        assert!((&out - &expected * &cg.transit).iter().all(|d| d.abs() < 1e-5));
+        assert_eq!(out, cg.transit_pow(6));
+        uncached.transit_pow_into(0, &mut out);
+        assert_eq!(out, DMatrix::identity(4, 4));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_mat_mul_par_into() {
+        let a = DMatrix::from_fn(40, 30, |i, j| ((i * 7 + j * 3) % 11) as f32 / 11.0);
+        let b = DMatrix::from_fn(30, 20, |i, j| ((i * 5 + j) % 13) as f32 / 13.0);
+        let mut serial = DMatrix::zeros(40, 20);
+        a.mul_to(&b, &mut serial);
+        let mut parallel = DMatrix::zeros(40, 20);
+        mat_mul_par_into(&a, &b, &mut parallel);
+        assert!((&parallel - &serial).iter().all(|d| d.abs() < 1e-4));
+        let mut empty = DMatrix::zeros(0, 0);
+        mat_mul_par_into(&DMatrix::zeros(0, 0), &DMatrix::zeros(0, 0), &mut empty);
+        assert_eq!(empty.shape(), (0, 0));
+
+        let chord_seq: Vec<Chord> = ["C", "G", "Am", "F", "Dm", "G7", "C", "Em", "F", "C"]
+            .into_iter()
+            .map(|s| Chord::try_from(s).unwrap())
+            .collect();
+        let cg = ChordGenerator::with_smoothing(&chord_seq, 0.1);
+        let mut out = DMatrix::zeros(0, 0);
+        cg.transit_pow_into(13, &mut out);
+        let mut serial = DMatrix::identity(cg.transit.nrows(), cg.transit.ncols());
+        for _ in 0..13 {
+            serial = &serial * &cg.transit;
+        }
+        assert!((&out - serial).iter().all(|d| d.abs() < 1e-5));
+    }
+
+    #[test]
+    fn test_train() {
+        let chord_seq = [
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("G").unwrap(),
+            Chord::try_from("Am").unwrap(),
+            Chord::try_from("F").unwrap(),
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("G").unwrap(),
+            Chord::try_from("Am").unwrap(),
+            Chord::try_from("F").unwrap(),
+        ];
+        let whole = ChordGenerator::new_cyclic(&chord_seq);
+        let mut halves = ChordGenerator::new_cyclic(&chord_seq[..4]);
+        halves.train(&chord_seq[4..]);
+        assert_eq!(halves.map_forward, whole.map_forward);
+        assert_eq!(halves.transit, whole.transit);
+
+        // new chords extend the vocabulary and invalidate cached powers
+        let mut cg = ChordGenerator::new_cyclic(&chord_seq[..4]);
+        cg.transit_pow(2);
+        cg.train(&[Chord::try_from("F").unwrap(), Chord::try_from("Dm").unwrap()]);
+        assert_eq!(cg.map_forward.len(), 5);
+        assert_eq!(cg.transit.shape(), (5, 5));
+        assert!(cg.transit_pow_cache.lock().is_empty());
+        assert_eq!(cg.transit[(4, 3)], 0.5);
+        assert_eq!(cg.transit[(3, 4)], 1.0);
+    }
+
+    #[test]
+    fn test_with_smoothing() {
+        let chord_seq = [
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("G").unwrap(),
+            Chord::try_from("Am").unwrap(),
+            Chord::try_from("F").unwrap(),
+        ];
+        let cg = ChordGenerator::with_smoothing(&chord_seq, 0.5);
+        assert!(cg.transit.iter().all(|&p| p > 0.0));
+        assert_eq!(cg.transit[(1, 0)], 0.5);
+        assert!((cg.transit[(2, 0)] - 0.5 / 3.0).abs() < 1e-6);
+        let cg0 = ChordGenerator::with_smoothing(&chord_seq, 0.0);
+        assert_eq!(cg0.transit, ChordGenerator::new(&chord_seq).transit);
+    }
+
+    #[test]
+    fn test_with_order() {
+        let chord_seq = [
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("G").unwrap(),
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("F").unwrap(),
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("G").unwrap(),
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("F").unwrap(),
+        ];
+        let is_loop = |seq: &[Chord]| (4..seq.len()).all(|i| seq[i] == seq[i - 4]) && seq[0] != seq[2];
+
+        // order 2 always alternates between C-G and C-F after the first chord
+        let cg2 = ChordGenerator::with_order(&chord_seq, 2);
+        assert_eq!(cg2.order(), 2);
+        for seed in 0..16 {
+            let seq = cg2.generate(chord_seq[0].clone(), 16, &mut StdRng::seed_from_u64(seed)).unwrap();
+            assert!(is_loop(&seq));
+        }
+
+        // order 1 chooses G or F independently after each C
+        let cg1 = ChordGenerator::new(&chord_seq);
+        assert!((0..16).any(|seed| {
+            let seq = cg1.generate(chord_seq[0].clone(), 16, &mut StdRng::seed_from_u64(seed)).unwrap();
+            !is_loop(&seq)
+        }));
+    }
+
+    #[test]
+    fn test_temperature() {
+        let chord_seq = [
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("G").unwrap(),
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("G").unwrap(),
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("F").unwrap(),
+        ];
+        let cg = ChordGenerator::new(&chord_seq);
+        for temperature in [0.0, -1.0, 1e-3] {
+            let options = SamplingOptions { temperature, ..Default::default() };
+            for seed in 0..16 {
+                let seq = cg.generate_with_options(chord_seq[0].clone(), 1, options, &mut StdRng::seed_from_u64(seed)).unwrap();
+                assert_eq!(seq[0], chord_seq[1]);
+            }
+        }
+
+        let flat = SamplingOptions { temperature: 1e6, ..Default::default() }.reweight(&[0.25, 0.75]);
+        assert!((flat[0] - flat[1]).abs() < 1e-3);
+        let same = SamplingOptions::default().reweight(&[0.25, 0.75, 0.0]);
+        assert!((same[0] / same[1] - 1.0 / 3.0).abs() < 1e-6);
+        assert_eq!(same[2], 0.0);
+    }
+
+    #[test]
+    fn test_top_p() {
+        let probability = [0.1, 0.5, 0.0, 0.3, 0.1];
+        let top = SamplingOptions { top_p: 0.0, ..Default::default() }.reweight(&probability);
+        assert_eq!(top, [0.0, 1.0, 0.0, 0.0, 0.0]);
+        let same = SamplingOptions { top_p: 1.0, ..Default::default() }.reweight(&probability);
+        assert_eq!(same, SamplingOptions::default().reweight(&probability));
+        assert!(same.iter().zip(probability).all(|(w, p)| (w - p / 0.5).abs() < 1e-6));
+        let nucleus = SamplingOptions { top_p: 0.75, ..Default::default() }.reweight(&probability);
+        assert!(nucleus[1] > 0.0 && nucleus[3] > 0.0);
+        assert_eq!([nucleus[0], nucleus[2], nucleus[4]], [0.0; 3]);
+        // top-p is applied after temperature, which flattens the distribution
+        let hot = SamplingOptions { temperature: 1e6, top_p: 0.75, ..Default::default() }.reweight(&probability);
+        assert_eq!(hot.iter().filter(|&&w| w > 0.0).count(), 3);
+
+        let chord_seq: Vec<Chord> = ["C", "G", "C", "G", "C", "F"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let cg = ChordGenerator::new(&chord_seq);
+        let options = SamplingOptions { top_p: 0.0, ..Default::default() };
+        for seed in 0..16 {
+            let seq = cg.generate_with_options(chord_seq[0].clone(), 1, options, &mut StdRng::seed_from_u64(seed)).unwrap();
+            assert_eq!(seq[0], chord_seq[1]);
+        }
+    }
+
+    #[test]
+    fn test_forbid_immediate_repeat() {
+        // C mostly loops on itself
+        let chord_seq: Vec<Chord> = ["C", "C", "C", "C", "C", "C", "G", "C", "F", "C"]
+            .into_iter()
+            .map(|s| Chord::try_from(s).unwrap())
+            .collect();
+        let options = SamplingOptions { forbid_immediate_repeat: true, ..Default::default() };
+        for cg in [ChordGenerator::new(&chord_seq), ChordGenerator::new_sparse(&chord_seq), ChordGenerator::with_order(&chord_seq, 2)] {
+            let seq = cg.generate_with_options(chord_seq[0].clone(), 200, options, &mut StdRng::seed_from_u64(0)).unwrap();
+            assert!(seq.windows(2).all(|window| window[0] != window[1]));
+            assert_ne!(seq[0], chord_seq[0]);
+        }
+
+        // D is only followed by D, so it is kept
+        let chord_seq: Vec<Chord> = ["D", "D"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let cg = ChordGenerator::new(&chord_seq);
+        let seq = cg.generate_with_options(chord_seq[0].clone(), 3, options, &mut StdRng::seed_from_u64(0)).unwrap();
+        assert_eq!(seq, [chord_seq[0].clone(), chord_seq[0].clone(), chord_seq[0].clone()]);
+    }
+
+    #[test]
+    fn test_fifth_boost() {
+        // G is followed by C once and by Am three times
+        let chord_seq: Vec<Chord> = ["G", "C", "G", "Am", "G", "Am", "G", "Am"].into_iter().map(|s| Chord::try_from(s).unwrap()).collect();
+        let (g, c) = (chord_seq[0].clone(), chord_seq[1].clone());
+        let count_c = |cg: &ChordGenerator, options: SamplingOptions| {
+            let mut rng = StdRng::seed_from_u64(0);
+            (0..1000).filter(|_| cg.generate_with_options(g.clone(), 1, options, &mut rng).unwrap()[0] == c).count()
+        };
+        for cg in [ChordGenerator::new(&chord_seq), ChordGenerator::new_sparse(&chord_seq)] {
+            let plain = count_c(&cg, SamplingOptions::default());
+            let boosted = count_c(&cg, SamplingOptions { fifth_boost: 9.0, ..Default::default() });
+            // 1/4 of the time without boost, and 9/12 of the time with boost
+            assert!((200..300).contains(&plain));
+            assert!((700..800).contains(&boosted));
+        }
+    }
+
+    #[test]
+    fn test_cadence() {
+        let chord_seq: Vec<Chord> = [
+            "C", "Am", "F", "G", "C", "Em", "Am", "Dm", "G7", "Em", "F", "C", "Am",
+        ]
+        .into_iter()
+        .map(|s| Chord::try_from(s).unwrap())
+        .collect();
+        let cg = ChordGenerator::new(&chord_seq);
+        let key = Key::major(3);
+        let options = SamplingOptions { cadence: Some(key), ..Default::default() };
+        for seed in 0..16 {
+            let seq = cg.generate_with_options(chord_seq[0].clone(), 6, options, &mut StdRng::seed_from_u64(seed)).unwrap();
+            assert_eq!(seq.len(), 6);
+            assert!(key.is_dominant(&seq[4]));
+            assert_eq!(seq[5], chord_seq[0]);
+        }
+        let seq = cg.generate_with_options(chord_seq[1].clone(), 1, options, &mut StdRng::seed_from_u64(0)).unwrap();
+        assert_eq!(seq, [chord_seq[0].clone()]);
+
+        // no dominant or tonic chord of F# minor is in the vocabulary, so the cadence falls back to
+        // any chord
+        let options = SamplingOptions { cadence: Some(Key::minor(9)), ..Default::default() };
+        assert_eq!(cg.generate_with_options(chord_seq[0].clone(), 6, options, &mut StdRng::seed_from_u64(0)).unwrap().len(), 6);
+    }
+
+    #[test]
+    fn test_generate_seeded() {
+        let chord_seq = [
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("G").unwrap(),
+            Chord::try_from("Am").unwrap(),
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("F").unwrap(),
+            Chord::try_from("G").unwrap(),
+        ];
+        let cg = ChordGenerator::new(&chord_seq);
+        let seq1 = cg.generate_seeded(chord_seq[0].clone(), 32, 7).unwrap();
+        let seq2 = cg.generate_seeded(chord_seq[0].clone(), 32, 7).unwrap();
+        assert_eq!(seq1, seq2);
+        assert_eq!(format!("{:?}", seq1), format!("{:?}", seq2));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_save_load() {
+        let chord_seq = [
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("G").unwrap(),
+            Chord::try_from("Am").unwrap(),
+            Chord::try_from("Em").unwrap(),
+            Chord::try_from("F").unwrap(),
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("F").unwrap(),
+            Chord::try_from("G7").unwrap(),
+        ];
+        let cg = ChordGenerator::new(&chord_seq);
+        let mut buf = Vec::new();
+        cg.save(&mut buf).unwrap();
+        let loaded = ChordGenerator::load(buf.as_slice()).unwrap();
+        assert_eq!(loaded.map_forward, cg.map_forward);
+        assert_eq!(loaded.transit, cg.transit);
+        let init = Chord::try_from("C").unwrap();
+        let seq1 = cg.generate(init.clone(), 32, &mut StdRng::seed_from_u64(42)).unwrap();
+        let seq2 = loaded.generate(init, 32, &mut StdRng::seed_from_u64(42)).unwrap();
+        assert_eq!(seq1, seq2);
+
+        // sparse occurrences are saved in the same format
+        let sparse = ChordGenerator::new_sparse(&chord_seq);
+        let mut sparse_buf = Vec::new();
+        sparse.save(&mut sparse_buf).unwrap();
+        let loaded = ChordGenerator::load(sparse_buf.as_slice()).unwrap();
+        assert!(loaded.is_sparse());
+        assert_eq!(loaded.dense_transit().as_ref(), &cg.transit);
+        assert_eq!(loaded.degree_stats(), cg.degree_stats());
+    }
+}
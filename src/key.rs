@@ -0,0 +1,234 @@
+use std::fmt::Display;
+
+use crate::{
+    chord::{Chord, ChordQuality},
+    error::{Error, Result},
+    note::{consume_to_note, note_string, Note},
+};
+
+/// Defines a key from its tonic and mode (major or minor).
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub struct Key {
+    tonic: Note,
+    major: bool,
+}
+
+impl Key {
+    /// Major key with the given tonic.
+    pub fn major(tonic: Note) -> Self {
+        Self {
+            tonic: tonic % 12,
+            major: true,
+        }
+    }
+
+    /// Minor key with the given tonic.
+    pub fn minor(tonic: Note) -> Self {
+        Self {
+            tonic: tonic % 12,
+            major: false,
+        }
+    }
+
+    pub fn tonic(&self) -> Note {
+        self.tonic
+    }
+
+    pub fn is_major(&self) -> bool {
+        self.major
+    }
+
+    /// Intervals of the 7 scale degrees above the tonic. Minor keys use the natural minor scale.
+    pub fn scale_intervals(&self) -> &'static [Note; 7] {
+        if self.major {
+            &[0, 2, 4, 5, 7, 9, 11]
+        } else {
+            &[0, 2, 3, 5, 7, 8, 10]
+        }
+    }
+
+    /// Whether notes in the key are spelled with flats rather than sharps. Keys whose major or
+    /// relative major key has flats in its key signature use flats, and so do C major and A minor,
+    /// since chords borrowed in these keys (e.g. bVII) are usually spelled with flats.
+    pub fn uses_flats(&self) -> bool {
+        let major_tonic = if self.major {
+            self.tonic
+        } else {
+            (self.tonic + 3) % 12
+        };
+        // C, F, Bb, Eb, Ab, Db
+        matches!(major_tonic, 3 | 8 | 1 | 6 | 11 | 4)
+    }
+
+    /// Whether the chord is a tonic chord of the key, i.e. a major chord on the tonic of a major key
+    /// or a minor chord on the tonic of a minor key, of any size.
+    pub fn is_tonic(&self, chord: &Chord) -> bool {
+        let quality = if self.major {
+            ChordQuality::Maj
+        } else {
+            ChordQuality::Min
+        };
+        chord.root() == self.tonic && chord.quality() == quality
+    }
+
+    /// Whether the chord is a dominant chord of the key, i.e. a major or dominant chord on the fifth
+    /// degree. Minor keys also use the major dominant of the harmonic minor scale.
+    pub fn is_dominant(&self, chord: &Chord) -> bool {
+        chord.root() == (self.tonic + 7) % 12
+            && matches!(chord.quality(), ChordQuality::Maj | ChordQuality::Dom)
+    }
+
+    /// Name of `note` spelled with sharps or flats according to the key.
+    pub fn note_name(&self, note: Note) -> &'static str {
+        let names = note_string(note);
+        if self.uses_flats() {
+            names[names.len() - 1]
+        } else {
+            names[0]
+        }
+    }
+
+    /// List the notes of the scale of the key. All notes are in modulo 12.
+    pub fn scale(&self) -> Vec<Note> {
+        self.scale_intervals()
+            .iter()
+            .map(|rel| (self.tonic + rel) % 12)
+            .collect()
+    }
+}
+
+/// Score how well the chord sequence fits each of the 24 major and minor keys, sorted from the best
+/// fitting key. Ties are kept in the order of major keys before minor keys, then by tonic.
+///
+/// Each chord scores 2 if its root is in the scale of the key, plus 1 for each of its notes in the
+/// scale. Tonic chords of the key (see `Key::is_tonic`) score 2 more, and 1 more if they start or
+/// end the sequence.
+pub fn detect_key_ranked(chords: &[Chord]) -> Vec<(Key, u32)> {
+    let mut ans: Vec<(Key, u32)> = (0..12)
+        .map(Key::major)
+        .chain((0..12).map(Key::minor))
+        .map(|key| {
+            let scale = key.scale();
+            let mut score = 0;
+            for (i, chord) in chords.iter().enumerate() {
+                if scale.contains(&chord.root()) {
+                    score += 2;
+                }
+                score += chord
+                    .notes()
+                    .iter()
+                    .filter(|note| scale.contains(note))
+                    .count() as u32;
+                if key.is_tonic(chord) {
+                    score += 2;
+                    if i == 0 || i == chords.len() - 1 {
+                        score += 1;
+                    }
+                }
+            }
+            (key, score)
+        })
+        .collect();
+    ans.sort_by(|(_, a), (_, b)| b.cmp(a));
+    ans
+}
+
+/// Detect the key that the chord sequence fits best. See `detect_key_ranked` for how keys are
+/// scored.
+pub fn detect_key(chords: &[Chord]) -> Key {
+    detect_key_ranked(chords)[0].0
+}
+
+impl TryFrom<&str> for Key {
+    type Error = Error;
+
+    /// Parse a key from its tonic, followed by "m" for minor keys, e.g. "C", "F#m".
+    fn try_from(value: &str) -> Result<Self> {
+        let (tonic, _, str_next) = consume_to_note(value)?;
+        match str_next {
+            "" | "M" | "maj" => Ok(Self::major(tonic)),
+            "m" | "min" => Ok(Self::minor(tonic)),
+            _ => Err(Error::InvalidFormat),
+        }
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            self.note_name(self.tonic),
+            if self.major { "" } else { "m" }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_string() {
+        assert_eq!(Key::try_from("C").unwrap(), Key::major(3));
+        assert_eq!(Key::try_from("F#m").unwrap(), Key::minor(9));
+        assert_eq!(Key::try_from("Cdim").unwrap_err(), Error::InvalidFormat);
+    }
+
+    #[test]
+    fn test_note_name() {
+        assert_eq!(Key::major(8).note_name(1), "Bb");
+        assert_eq!(Key::major(5).note_name(1), "A#");
+        assert_eq!(Key::minor(5).note_name(1), "Bb");
+        assert_eq!(Key::minor(7).note_name(6), "D#");
+        assert_eq!(Key::major(5).note_name(5), "D");
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", Key::major(3)), "C");
+        assert_eq!(format!("{}", Key::major(1)), "Bb");
+        assert_eq!(format!("{}", Key::minor(9)), "F#m");
+    }
+
+    #[test]
+    fn test_detect_key() {
+        let chords: Vec<Chord> = ["C", "G", "Am", "F"]
+            .into_iter()
+            .map(|s| Chord::try_from(s).unwrap())
+            .collect();
+        assert_eq!(detect_key(&chords), Key::major(3));
+        let ranked = detect_key_ranked(&chords);
+        assert_eq!(ranked.len(), 24);
+        assert_eq!(ranked[0].0, Key::major(3));
+        assert!(ranked.windows(2).all(|w| w[0].1 >= w[1].1));
+
+        let chords: Vec<Chord> = ["Am", "Dm", "E7", "Am"]
+            .into_iter()
+            .map(|s| Chord::try_from(s).unwrap())
+            .collect();
+        assert_eq!(detect_key(&chords), Key::minor(0));
+
+        // a dominant seventh is not a tonic chord, so G7 alone fits C major better than G major
+        let chords = [Chord::try_from("G7").unwrap()];
+        assert_eq!(detect_key(&chords), Key::major(3));
+    }
+
+    #[test]
+    fn test_scale() {
+        assert_eq!(Key::major(3).scale(), [3, 5, 7, 8, 10, 0, 2]);
+        assert_eq!(Key::minor(0).scale(), [0, 2, 3, 5, 7, 8, 10]);
+    }
+
+    #[test]
+    fn test_cadence_chords() {
+        let c_major = Key::major(3);
+        assert!(c_major.is_tonic(&Chord::try_from("CM7").unwrap()));
+        assert!(!c_major.is_tonic(&Chord::try_from("Cm").unwrap()));
+        assert!(c_major.is_dominant(&Chord::try_from("G7").unwrap()));
+        assert!(!c_major.is_dominant(&Chord::try_from("Gm").unwrap()));
+        let a_minor = Key::minor(0);
+        assert!(a_minor.is_tonic(&Chord::try_from("Am").unwrap()));
+        assert!(a_minor.is_dominant(&Chord::try_from("E").unwrap()));
+    }
+}
@@ -0,0 +1,39 @@
+//! Generate chord progressions using Markov chain model.
+//!
+//! Chords are parsed from their names, e.g. "Am7" or "C/E", and a `ChordGenerator` is trained from
+//! sequences of chords to generate new progressions.
+//!
+//! ```
+//! use markov_chord::{parse_progression, ChordGenerator};
+//!
+//! let chords = parse_progression("C G Am F | C G F C").unwrap();
+//! let generator = ChordGenerator::new(&chords);
+//! let generated = generator.generate_seeded(chords[0].clone(), 8, 42).unwrap();
+//! assert_eq!(generated.len(), 8);
+//! assert!(generated.iter().all(|chord| chords.contains(chord)));
+//! ```
+
+pub mod abc;
+pub mod chord;
+pub mod error;
+pub mod generator;
+pub mod key;
+pub mod lilypond;
+pub mod midi;
+pub mod musicxml;
+pub mod nashville;
+pub mod note;
+pub mod progression;
+pub mod progressions;
+mod rhythm;
+#[cfg(feature = "synth")]
+pub mod synth;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use chord::{Chord, ChordQuality, HarmonicFunction};
+pub use error::{Error, Result};
+pub use generator::{ChordGenerator, SamplingOptions};
+pub use key::Key;
+pub use note::Note;
+pub use progression::{format_progression, parse_chordpro, parse_progression};
@@ -0,0 +1,164 @@
+use crate::{
+    chord::{Chord, ChordQuality},
+    note::Note,
+};
+
+/// LilyPond modifier of chords of each quality and number of notes.
+const MODIFIERS: &[(ChordQuality, u8, &str)] = &[
+    (ChordQuality::Maj, 3, ""),
+    (ChordQuality::Maj, 4, ":maj7"),
+    (ChordQuality::Maj, 5, ":maj9"),
+    (ChordQuality::Maj, 6, ":maj11"),
+    (ChordQuality::Maj, 7, ":maj13"),
+    (ChordQuality::Min, 3, ":m"),
+    (ChordQuality::Min, 4, ":m7"),
+    (ChordQuality::Min, 5, ":m9"),
+    (ChordQuality::Min, 6, ":m11"),
+    (ChordQuality::Min, 7, ":m13"),
+    (ChordQuality::Dom, 3, ""),
+    (ChordQuality::Dom, 4, ":7"),
+    (ChordQuality::Dom, 5, ":9"),
+    (ChordQuality::Dom, 6, ":11"),
+    (ChordQuality::Dom, 7, ":13"),
+    (ChordQuality::Aug, 3, ":aug"),
+    (ChordQuality::Aug, 4, ":aug7"),
+    (ChordQuality::Dim, 3, ":dim"),
+    (ChordQuality::Dim, 4, ":dim7"),
+    (ChordQuality::HalfDim, 3, ":dim"),
+    (ChordQuality::HalfDim, 4, ":m7.5-"),
+    (ChordQuality::Sus2, 3, ":sus2"),
+    (ChordQuality::Sus4, 3, ":sus4"),
+    (ChordQuality::Power, 2, ":1.5"),
+];
+
+/// LilyPond name of a note. LilyPond names notes from C, while note 0 is A.
+fn note_name(note: Note) -> &'static str {
+    [
+        "a", "ais", "b", "c", "cis", "d", "dis", "e", "f", "fis", "g", "gis",
+    ][(note % 12) as usize]
+}
+
+/// Look up the modifier table.
+fn table_modifier(quality: ChordQuality, note_num: u8) -> Option<&'static str> {
+    MODIFIERS
+        .iter()
+        .find(|(q, n, _)| *q == quality && *n == note_num)
+        .map(|(_, _, modifier)| *modifier)
+}
+
+/// LilyPond modifier of a chord, e.g. ":m7". Chords missing in the table use the modifier of the
+/// triad of same quality followed by the number of the chord, e.g. ":aug9", except that suspended
+/// chords put the number before "sus", e.g. ":9sus2", and half-diminished chords are minor chords
+/// with a flat fifth, e.g. ":m9.5-".
+fn modifier(chord: &Chord) -> String {
+    let size = chord.note_num() * 2 - 1;
+    let mut ans = match table_modifier(chord.quality(), chord.note_num()) {
+        Some(modifier) => modifier.to_string(),
+        None => match chord.quality() {
+            ChordQuality::Sus2 => format!(":{}sus2", size),
+            ChordQuality::Sus4 => format!(":{}sus4", size),
+            ChordQuality::HalfDim => format!(":m{}.5-", size),
+            quality => match table_modifier(quality, 3) {
+                Some("") | None => format!(":{}", size),
+                Some(modifier) => format!("{}{}", modifier, size),
+            },
+        },
+    };
+    for degree in chord.added() {
+        if *degree == 6 && chord.note_num() == 3 {
+            ans = if ans.is_empty() {
+                ":6".to_string()
+            } else {
+                ans + "6"
+            };
+        } else if ans.is_empty() {
+            ans = format!(":5.{}", degree);
+        } else {
+            ans = format!("{}.{}", ans, degree);
+        }
+    }
+    for &(degree, shift) in chord.altered() {
+        let sign = if shift < 0 { "-" } else { "+" };
+        ans = if ans.is_empty() {
+            format!(":5.{}{}", degree, sign)
+        } else {
+            format!("{}.{}{}", ans, degree, sign)
+        };
+    }
+    if !chord.omitted().is_empty() {
+        let omitted: Vec<String> = chord.omitted().iter().map(u8::to_string).collect();
+        ans = format!(
+            "{}^{}",
+            if ans.is_empty() { ":" } else { &ans },
+            omitted.join(".")
+        );
+    }
+    ans
+}
+
+/// Write the chord sequence as a LilyPond `\chordmode` block, with one chord per whole note.
+pub fn to_lilypond(chords: &[Chord]) -> String {
+    let body: Vec<String> = chords
+        .iter()
+        .map(|chord| {
+            let name = format!("{}1{}", note_name(chord.root()), modifier(chord));
+            match chord.bass() {
+                Some(bass) => format!("{}/{}", name, note_name(bass)),
+                None => name,
+            }
+        })
+        .collect();
+    format!("\\chordmode {{ {} }}", body.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_lilypond() {
+        let chords = [
+            Chord::try_from("Dm7").unwrap(),
+            Chord::try_from("G7").unwrap(),
+            Chord::try_from("CM7").unwrap(),
+        ];
+        assert_eq!(to_lilypond(&chords), "\\chordmode { d1:m7 g1:7 c1:maj7 }");
+        let chords = [
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("G").unwrap(),
+            Chord::try_from("Am").unwrap(),
+            Chord::try_from("F").unwrap(),
+        ];
+        assert_eq!(to_lilypond(&chords), "\\chordmode { c1 g1 a1:m f1 }");
+    }
+
+    #[test]
+    fn test_modifier() {
+        let half_dim = Chord::try_from("Bø7").unwrap();
+        assert_eq!(modifier(&half_dim), ":m7.5-");
+        let c_6 = Chord::try_from("Cm6").unwrap();
+        assert_eq!(modifier(&c_6), ":m6");
+        let c_add_9 = Chord::try_from("Cadd9").unwrap();
+        assert_eq!(modifier(&c_add_9), ":5.9");
+        let aug_9 = Chord::try_from("Caug9").unwrap();
+        assert_eq!(modifier(&aug_9), ":aug9");
+        let sus_4_7 = Chord::try_from("C7sus4").unwrap();
+        assert_eq!(modifier(&sus_4_7), ":7sus4");
+        let sus_2_9 = Chord::try_from("C9sus2").unwrap();
+        assert_eq!(modifier(&sus_2_9), ":9sus2");
+        let half_dim_9 = Chord::try_from("Cø9").unwrap();
+        assert_eq!(modifier(&half_dim_9), ":m9.5-");
+        let half_dim_13 = Chord::try_from("Cø13").unwrap();
+        assert_eq!(modifier(&half_dim_13), ":m13.5-");
+        let power = Chord::try_from("C5").unwrap();
+        assert_eq!(modifier(&power), ":1.5");
+        let altered = Chord::try_from("C7#5b9").unwrap();
+        assert_eq!(modifier(&altered), ":7.5+.9-");
+        let no_5 = Chord::try_from("C7(no5)").unwrap();
+        assert_eq!(modifier(&no_5), ":7^5");
+        let no_3 = Chord::try_from("C(no3)").unwrap();
+        assert_eq!(modifier(&no_3), ":^3");
+        let c_over_e = Chord::try_from("C/E").unwrap();
+        assert_eq!(to_lilypond(&[c_over_e]), "\\chordmode { c1/e }");
+    }
+}
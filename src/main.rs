@@ -1,40 +1,79 @@
-use std::{fs::File, io::Read};
+use std::{error::Error, fs, path::PathBuf, process};
 
-use chord::Chord;
-use generator::ChordGenerator;
+use clap::{Parser, ValueEnum};
+use markov_chord::{format_progression, parse_progression, Chord, ChordGenerator};
+use rand::{rngs::StdRng, SeedableRng};
 
-mod chord;
-mod error;
-mod generator;
-mod note;
+#[derive(Clone, Copy, ValueEnum)]
+enum Mode {
+    /// Generate chords following the left chord
+    Generate,
+    /// Generate chords between the left chord and the right chord
+    Range,
+}
+
+/// Generate chord progressions using Markov chain model.
+#[derive(Parser)]
+struct Args {
+    /// File of chord progressions to train the model
+    #[arg(short, long, default_value = "chord.txt")]
+    input: PathBuf,
+    /// Number of chords generated after the left chord in generate mode, or number of chords of the
+    /// whole progression, including the left and right chords, in range mode
+    #[arg(short, long, default_value_t = 8)]
+    length: usize,
+    #[arg(short, long, value_enum, default_value_t = Mode::Range)]
+    mode: Mode,
+    /// The chord to start with
+    #[arg(long, default_value = "F")]
+    left: String,
+    /// The chord to end with in range mode
+    #[arg(long, default_value = "C")]
+    right: String,
+    /// Seed of the random number generator
+    #[arg(long)]
+    seed: Option<u64>,
+}
 
-fn read_generator(file: &mut File) -> ChordGenerator {
-    let mut file_string = String::new();
-    file.read_to_string(&mut file_string).unwrap();
-    let chord_seq = file_string
-        .split([',', '|', ' '])
-        .filter(|s| !s.is_empty())
-        .map(|s| Chord::try_from(s).unwrap())
-        .collect::<Vec<_>>();
-    ChordGenerator::new(&chord_seq)
+fn read_generator(path: &PathBuf) -> Result<ChordGenerator, String> {
+    let file_string = fs::read_to_string(path)
+        .map_err(|err| format!("Cannot read file {}: {}", path.display(), err))?;
+    let chord_seq = parse_progression(&file_string)
+        .map_err(|err| format!("Cannot read file {}: {}", path.display(), err))?;
+    Ok(ChordGenerator::new(&chord_seq))
+}
+
+fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let generator = read_generator(&args.input)?;
+    let left_chord = Chord::try_from(args.left.as_str())
+        .map_err(|err| format!("Cannot parse chord {}: {}", args.left, err))?;
+    let mut rng = match args.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let mut progression = vec![left_chord.clone()];
+    match args.mode {
+        Mode::Generate => {
+            let generated = generator
+                .generate(left_chord, args.length, &mut rng)?;
+            progression.extend(generated);
+        }
+        Mode::Range => {
+            let right_chord = Chord::try_from(args.right.as_str())
+                .map_err(|err| format!("Cannot parse chord {}: {}", args.right, err))?;
+            let generated = generator
+                .generate_range(left_chord, right_chord.clone(), args.length, &mut rng)?;
+            progression.extend(generated);
+            progression.push(right_chord);
+        }
+    }
+    println!("{}", format_progression(&progression, " "));
+    Ok(())
 }
 
 fn main() {
-    let mut generator = read_generator(&mut File::open("chord.txt").unwrap());
-    let left_chord = Chord::try_from("F").unwrap();
-    let right_chord = Chord::try_from("C").unwrap();
-    let generated = generator.generate_range(
-        left_chord.clone(),
-        right_chord.clone(),
-        8,
-        &mut rand::thread_rng(),
-    );
-    // let generated = generator.generate(
-    //     left_chord.clone(), 16, &mut rand::thread_rng()
-    // );
-    print!("{} ", left_chord);
-    for chord in generated.unwrap() {
-        print!("{} ", chord);
+    if let Err(err) = run(Args::parse()) {
+        eprintln!("error: {}", err);
+        process::exit(1);
     }
-    println!("{} ", right_chord);
 }
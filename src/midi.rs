@@ -0,0 +1,411 @@
+use std::io::{Error, ErrorKind, Read, Result, Write};
+
+use crate::{chord::Chord, note::Note, rhythm};
+
+/// Options of MIDI file export.
+#[derive(Debug, Clone, Copy)]
+pub struct MidiOptions {
+    pub tempo: u32,          // beats per minute
+    pub velocity: u8,        // velocity of every note
+    pub ticks_per_beat: u16, // MIDI time division
+    pub base_note: u8,       // MIDI note number of note 0 (A)
+}
+
+impl Default for MidiOptions {
+    /// Default options play at 120 BPM, with note A mapped to A3 (MIDI 57).
+    fn default() -> Self {
+        Self {
+            tempo: 120,
+            velocity: 80,
+            ticks_per_beat: 480,
+            base_note: 57,
+        }
+    }
+}
+
+/// Write a variable-length quantity used by MIDI delta times.
+fn write_var_len(buf: &mut Vec<u8>, mut value: u32) {
+    let mut bytes = vec![(value & 0x7f) as u8];
+    value >>= 7;
+    while value > 0 {
+        bytes.push((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.extend(bytes.iter().rev());
+}
+
+/// Write the chord sequence as a single-track Standard MIDI File. Each chord is played as a block
+/// chord lasting `durations[i]` beats, or a whole note (4 beats) if `durations` is shorter than
+/// `chords`.
+///
+/// `opts.base_note` and `opts.velocity` must be at most 127. Stacked notes above the MIDI range are
+/// clamped into it.
+pub fn write_midi<W: Write>(
+    chords: &[Chord],
+    durations: &[f32],
+    out: &mut W,
+    opts: MidiOptions,
+) -> Result<()> {
+    if opts.base_note > 127 || opts.velocity > 127 {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "base note and velocity must be at most 127",
+        ));
+    }
+    let mut track = Vec::new();
+
+    // tempo meta event, in microseconds per beat
+    let tempo = 60_000_000 / opts.tempo.max(1);
+    write_var_len(&mut track, 0);
+    track.extend([0xff, 0x51, 0x03]);
+    track.extend(&tempo.to_be_bytes()[1..]);
+
+    for (i, chord) in chords.iter().enumerate() {
+        let ticks = (rhythm::duration(durations, i).max(0.0) * opts.ticks_per_beat as f32).round();
+        let notes = chord.midi_notes_from_base(opts.base_note);
+        for &note in &notes {
+            write_var_len(&mut track, 0);
+            track.extend([0x90, note, opts.velocity]);
+        }
+        for (i, &note) in notes.iter().enumerate() {
+            write_var_len(&mut track, if i == 0 { ticks as u32 } else { 0 });
+            track.extend([0x80, note, 0]);
+        }
+    }
+
+    // end of track
+    write_var_len(&mut track, 0);
+    track.extend([0xff, 0x2f, 0x00]);
+
+    out.write_all(b"MThd")?;
+    out.write_all(&6u32.to_be_bytes())?;
+    out.write_all(&0u16.to_be_bytes())?; // format 0
+    out.write_all(&1u16.to_be_bytes())?; // one track
+    out.write_all(&opts.ticks_per_beat.to_be_bytes())?;
+    out.write_all(b"MTrk")?;
+    out.write_all(&(track.len() as u32).to_be_bytes())?;
+    out.write_all(&track)
+}
+
+/// Error of a MIDI file that cannot be read.
+fn invalid_data(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+/// Read a variable-length quantity starting at `index`, and move `index` after it.
+fn read_var_len(data: &[u8], index: &mut usize) -> Result<u32> {
+    let mut value = 0;
+    loop {
+        let byte = *data
+            .get(*index)
+            .ok_or_else(|| invalid_data("unexpected end of track"))?;
+        *index += 1;
+        value = (value << 7) | (byte & 0x7f) as u32;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+}
+
+/// Read the note-on events of a track as pairs of time in ticks and MIDI note number. Notes on the
+/// percussion channel (channel 10) are skipped since they are not pitches.
+fn read_track_notes(track: &[u8]) -> Result<Vec<(u32, u8)>> {
+    let mut ans = Vec::new();
+    let mut index = 0;
+    let mut time = 0;
+    let mut running_status = None;
+    while index < track.len() {
+        time += read_var_len(track, &mut index)?;
+        let status = match track.get(index) {
+            Some(&status) if status >= 0x80 => {
+                index += 1;
+                status
+            }
+            _ => running_status.ok_or_else(|| invalid_data("missing status byte"))?,
+        };
+        match status {
+            0xff => {
+                // meta event: type, length, data
+                index += 1;
+                index += read_var_len(track, &mut index)? as usize;
+                running_status = None;
+            }
+            0xf0 | 0xf7 => {
+                // system exclusive event: length, data
+                index += read_var_len(track, &mut index)? as usize;
+                running_status = None;
+            }
+            _ => {
+                running_status = Some(status);
+                let data_len = match status & 0xf0 {
+                    0xc0 | 0xd0 => 1,
+                    _ => 2,
+                };
+                let data = track
+                    .get(index..index + data_len)
+                    .ok_or_else(|| invalid_data("unexpected end of track"))?;
+                // note-on with velocity 0 is a note-off
+                if status & 0xf0 == 0x90 && status & 0x0f != 9 && data[1] > 0 {
+                    ans.push((time, data[0]));
+                }
+                index += data_len;
+            }
+        }
+    }
+    Ok(ans)
+}
+
+/// Recover the chord sequence of a Standard MIDI File, e.g. to train a generator from real music.
+///
+/// Notes starting within a sixteenth note of the first note of a chord are grouped as the chord,
+/// and each group is identified by `Chord::from_notes` with the lowest note as the preferred root.
+/// Only the start of notes is considered, so notes held over from the previous chord (overlapping
+/// or sustained) do not join the next chord. Groups that are not a known chord, e.g. single notes
+/// of a melody, are skipped.
+pub fn chords_from_midi<R: Read>(input: &mut R) -> Result<Vec<Chord>> {
+    let mut bytes = Vec::new();
+    input.read_to_end(&mut bytes)?;
+    if bytes.len() < 14 || &bytes[0..4] != b"MThd" {
+        return Err(invalid_data("missing MIDI header"));
+    }
+    let division = u16::from_be_bytes([bytes[12], bytes[13]]);
+    // SMPTE time division does not count ticks per beat, so a common resolution is assumed
+    let ticks_per_beat = if division & 0x8000 == 0 {
+        division
+    } else {
+        480
+    } as u32;
+
+    let mut notes = Vec::new();
+    let mut index = 8 + u32::from_be_bytes(bytes[4..8].try_into().unwrap()) as usize;
+    while index + 8 <= bytes.len() {
+        let len = u32::from_be_bytes(bytes[index + 4..index + 8].try_into().unwrap()) as usize;
+        let chunk = bytes
+            .get(index + 8..index + 8 + len)
+            .ok_or_else(|| invalid_data("unexpected end of chunk"))?;
+        if &bytes[index..index + 4] == b"MTrk" {
+            notes.extend(read_track_notes(chunk)?);
+        }
+        index += 8 + len;
+    }
+    notes.sort_unstable();
+
+    let window = ticks_per_beat / 4;
+    let mut groups: Vec<(u32, Vec<u8>)> = Vec::new();
+    for (time, note) in notes {
+        match groups.last_mut() {
+            Some((start, group)) if time - *start <= window => group.push(note),
+            _ => groups.push((time, vec![note])),
+        }
+    }
+    Ok(groups
+        .into_iter()
+        .filter_map(|(_, mut group)| {
+            group.sort_unstable();
+            // MIDI note 69 is A, which is note 0
+            let group: Vec<Note> = group
+                .iter()
+                .map(|&note| ((note + 3) % 12) as Note)
+                .collect();
+            Chord::from_notes(&group)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Read back the note-on events of a MIDI file written by `write_midi`, grouped by the time
+    /// they are played.
+    fn read_note_on(bytes: &[u8]) -> Vec<Vec<u8>> {
+        read_timed_note_on(bytes)
+            .0
+            .into_iter()
+            .map(|(_, notes)| notes)
+            .collect()
+    }
+
+    /// Read back the note-on events like `read_note_on` with the time they are played in ticks,
+    /// together with the length of the track in ticks.
+    fn read_timed_note_on(bytes: &[u8]) -> (Vec<(u32, Vec<u8>)>, u32) {
+        assert_eq!(&bytes[0..4], b"MThd");
+        assert_eq!(&bytes[14..18], b"MTrk");
+        let len = u32::from_be_bytes(bytes[18..22].try_into().unwrap()) as usize;
+        let track = &bytes[22..22 + len];
+        let mut index = 0;
+        let mut time = 0;
+        let mut ans: Vec<(u32, Vec<u8>)> = Vec::new();
+        while index < track.len() {
+            let mut delta = 0;
+            loop {
+                delta = (delta << 7) | (track[index] & 0x7f) as u32;
+                index += 1;
+                if track[index - 1] & 0x80 == 0 {
+                    break;
+                }
+            }
+            time += delta;
+            match track[index] {
+                0xff => index += 3 + track[index + 2] as usize,
+                0x90 => {
+                    match ans.last_mut() {
+                        Some((t, notes)) if *t == time => notes.push(track[index + 1]),
+                        _ => ans.push((time, vec![track[index + 1]])),
+                    }
+                    index += 3;
+                }
+                _ => index += 3,
+            }
+        }
+        (ans, time)
+    }
+
+    #[test]
+    fn test_write_midi() {
+        let chords = [
+            Chord::try_from("A").unwrap(),
+            Chord::try_from("G7").unwrap(),
+            Chord::try_from("C/E").unwrap(),
+        ];
+        let mut bytes = Vec::new();
+        write_midi(&chords, &[], &mut bytes, MidiOptions::default()).unwrap();
+        let note_on = read_note_on(&bytes);
+        assert_eq!(note_on.len(), 3);
+        assert_eq!(note_on[0], [57, 61, 64]);
+        assert_eq!(note_on[1], [67, 71, 74, 77]);
+        assert_eq!(note_on[2], [64, 72, 79]);
+    }
+
+    #[test]
+    fn test_write_midi_range() {
+        let chords = [
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("G7").unwrap(),
+        ];
+        let opts = MidiOptions {
+            base_note: 120,
+            ..Default::default()
+        };
+        let mut bytes = Vec::new();
+        write_midi(&chords, &[], &mut bytes, opts).unwrap();
+        let note_on = read_note_on(&bytes);
+        assert_eq!(note_on[0], [123, 127]);
+        assert_eq!(note_on[1], [127]);
+
+        for opts in [
+            MidiOptions {
+                base_note: 128,
+                ..Default::default()
+            },
+            MidiOptions {
+                velocity: 128,
+                ..Default::default()
+            },
+        ] {
+            assert_eq!(
+                write_midi(&chords, &[], &mut Vec::new(), opts)
+                    .unwrap_err()
+                    .kind(),
+                ErrorKind::InvalidInput
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_midi_durations() {
+        let chords = [
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("G").unwrap(),
+            Chord::try_from("Am").unwrap(),
+            Chord::try_from("F").unwrap(),
+        ];
+        let mut bytes = Vec::new();
+        write_midi(
+            &chords,
+            &[2.0, 1.0, 0.5],
+            &mut bytes,
+            MidiOptions::default(),
+        )
+        .unwrap();
+        let (note_on, length) = read_timed_note_on(&bytes);
+        let times: Vec<u32> = note_on.iter().map(|&(time, _)| time).collect();
+        assert_eq!(times, [0, 960, 1440, 1680]);
+        // the last chord defaults to a whole note
+        assert_eq!(length, 1680 + 1920);
+    }
+
+    #[test]
+    fn test_chords_from_midi() {
+        let chords = [
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("Am7").unwrap(),
+            Chord::try_from("F/A").unwrap(),
+            Chord::try_from("G7").unwrap(),
+            Chord::try_from("C").unwrap(),
+        ];
+        let mut bytes = Vec::new();
+        write_midi(&chords, &[], &mut bytes, MidiOptions::default()).unwrap();
+        assert_eq!(chords_from_midi(&mut bytes.as_slice()).unwrap(), chords);
+    }
+
+    #[test]
+    fn test_chords_from_midi_overlap() {
+        // C major arpeggiated slightly and held under an E melody note, then G major with running
+        // status, on two tracks
+        let mut track = Vec::new();
+        for (delta, event) in [
+            (0, [0x90, 60, 80]),
+            (10, [0x90, 64, 80]),
+            (10, [0x90, 67, 80]),
+            (240, [0x90, 76, 80]),
+            (100, [0x80, 76, 0]),
+            (140, [0x80, 60, 0]),
+        ] {
+            write_var_len(&mut track, delta);
+            track.extend(event);
+        }
+        track.extend([0x00, 0x90, 55, 80]);
+        for (delta, event) in [(0, [59, 80]), (0, [62, 80]), (480, [55, 0])] {
+            write_var_len(&mut track, delta);
+            track.extend(event);
+        }
+        track.extend([0x00, 0xff, 0x2f, 0x00]);
+        let mut drums = Vec::new();
+        drums.extend([
+            0x00, 0x99, 36, 100, 0x00, 0x99, 38, 100, 0x00, 0xff, 0x2f, 0x00,
+        ]);
+
+        let mut bytes = Vec::new();
+        bytes.extend(b"MThd");
+        bytes.extend(6u32.to_be_bytes());
+        bytes.extend(1u16.to_be_bytes());
+        bytes.extend(2u16.to_be_bytes());
+        bytes.extend(480u16.to_be_bytes());
+        for track in [track, drums] {
+            bytes.extend(b"MTrk");
+            bytes.extend((track.len() as u32).to_be_bytes());
+            bytes.extend(track);
+        }
+        assert_eq!(
+            chords_from_midi(&mut bytes.as_slice()).unwrap(),
+            [Chord::try_from("C").unwrap(), Chord::try_from("G").unwrap()]
+        );
+        assert_eq!(
+            chords_from_midi(&mut b"MTrk".as_slice())
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn test_write_var_len() {
+        let mut buf = Vec::new();
+        write_var_len(&mut buf, 0);
+        write_var_len(&mut buf, 0x7f);
+        write_var_len(&mut buf, 0x80);
+        write_var_len(&mut buf, 480);
+        assert_eq!(buf, [0x00, 0x7f, 0x81, 0x00, 0x83, 0x60]);
+    }
+}
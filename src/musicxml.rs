@@ -0,0 +1,365 @@
+use crate::{
+    chord::{Chord, ChordQuality},
+    key::Key,
+    note::Note,
+    rhythm,
+};
+
+/// Rests of each length in sixteenth notes, from the longest, with their MusicXML `<type>` and
+/// whether they are dotted.
+const RESTS: &[(u32, &str, bool)] = &[
+    (16, "whole", false),
+    (12, "half", true),
+    (8, "half", false),
+    (6, "quarter", true),
+    (4, "quarter", false),
+    (3, "eighth", true),
+    (2, "eighth", false),
+    (1, "16th", false),
+];
+
+/// MusicXML `<kind>` of chords of each quality and number of notes.
+const KINDS: &[(ChordQuality, u8, &str)] = &[
+    (ChordQuality::Maj, 3, "major"),
+    (ChordQuality::Maj, 4, "major-seventh"),
+    (ChordQuality::Maj, 5, "major-ninth"),
+    (ChordQuality::Maj, 6, "major-11th"),
+    (ChordQuality::Maj, 7, "major-13th"),
+    (ChordQuality::Min, 3, "minor"),
+    (ChordQuality::Min, 4, "minor-seventh"),
+    (ChordQuality::Min, 5, "minor-ninth"),
+    (ChordQuality::Min, 6, "minor-11th"),
+    (ChordQuality::Min, 7, "minor-13th"),
+    (ChordQuality::Dom, 3, "major"),
+    (ChordQuality::Dom, 4, "dominant"),
+    (ChordQuality::Dom, 5, "dominant-ninth"),
+    (ChordQuality::Dom, 6, "dominant-11th"),
+    (ChordQuality::Dom, 7, "dominant-13th"),
+    (ChordQuality::Aug, 3, "augmented"),
+    (ChordQuality::Aug, 4, "augmented-seventh"),
+    (ChordQuality::Dim, 3, "diminished"),
+    (ChordQuality::Dim, 4, "diminished-seventh"),
+    (ChordQuality::HalfDim, 3, "diminished"),
+    (ChordQuality::HalfDim, 4, "half-diminished"),
+    (ChordQuality::Sus2, 3, "suspended-second"),
+    (ChordQuality::Sus4, 3, "suspended-fourth"),
+    (ChordQuality::Power, 2, "power"),
+];
+
+/// MusicXML `<kind>` of a chord and its number of notes. Chords missing in the table use the kind
+/// of the largest chord of the same quality in the table, and their other chord tones are added as
+/// degrees (see `extra_degrees`).
+fn kind(chord: &Chord) -> (&'static str, u8) {
+    KINDS
+        .iter()
+        .filter(|(quality, note_num, _)| {
+            *quality == chord.quality() && *note_num <= chord.note_num().max(3)
+        })
+        .max_by_key(|(_, note_num, _)| *note_num)
+        .map_or(("major", 3), |&(_, note_num, kind)| (kind, note_num))
+}
+
+/// Chord tones of a chord above the notes of its kind, as scale degrees and their alteration from
+/// the tones of a dominant chord, which MusicXML degrees are relative to, e.g. (7, 0) and (9, 0)
+/// for C9sus4. Altered tones include their alteration, and omitted tones are left out.
+fn extra_degrees(chord: &Chord) -> Vec<(u8, i8)> {
+    let quality = chord.quality();
+    let dominant = ChordQuality::Dom.relative_pitch();
+    let (_, kind_note_num) = kind(chord);
+    (kind_note_num as usize..chord.note_num() as usize)
+        .map(|i| (quality.degrees()[i], quality.relative_pitch()[i]))
+        .filter(|(degree, _)| !chord.omitted().contains(degree))
+        .map(|(degree, pitch)| {
+            let shift = chord
+                .altered()
+                .iter()
+                .find(|&&(altered, _)| altered == degree)
+                .map_or(0, |&(_, shift)| shift);
+            let alter = pitch as i8 - dominant[(degree as usize - 1) / 2] as i8 + shift;
+            (degree, alter)
+        })
+        .collect()
+}
+
+/// Step and alteration of a note spelled in `key`, e.g. ("B", -1) for Bb.
+fn step_alter(note: Note, key: Key) -> (&'static str, i8) {
+    let name = key.note_name(note);
+    let alter = match name.get(1..) {
+        Some("#") => 1,
+        Some("b") => -1,
+        _ => 0,
+    };
+    (&name[..1], alter)
+}
+
+/// Number of sharps (positive) or flats (negative) in the key signature of `key`.
+fn fifths(key: Key) -> i32 {
+    let major_tonic = if key.is_major() {
+        key.tonic()
+    } else {
+        (key.tonic() + 3) % 12
+    };
+    // C is note 3, and every fifth up adds a sharp
+    let sharps = ((major_tonic as i32 - 3) * 7).rem_euclid(12);
+    if sharps > 0 && key.uses_flats() {
+        sharps - 12
+    } else {
+        sharps
+    }
+}
+
+/// MusicXML `<harmony>` element of a chord.
+fn harmony(chord: &Chord, key: Key) -> String {
+    let (step, alter) = step_alter(chord.root(), key);
+    let mut ans = format!("<harmony><root><root-step>{}</root-step>", step);
+    if alter != 0 {
+        ans += &format!("<root-alter>{}</root-alter>", alter);
+    }
+    let (kind, kind_note_num) = kind(chord);
+    // degrees of chord tones above the kind, which are added rather than altered or omitted
+    let extra = &chord.quality().degrees()[kind_note_num as usize..chord.note_num() as usize];
+    ans += &format!("</root><kind>{}</kind>", kind);
+    if let Some(bass) = chord.bass() {
+        let (step, alter) = step_alter(bass, key);
+        ans += &format!("<bass><bass-step>{}</bass-step>", step);
+        if alter != 0 {
+            ans += &format!("<bass-alter>{}</bass-alter>", alter);
+        }
+        ans += "</bass>";
+    }
+    for (degree, alter) in extra_degrees(chord) {
+        ans += &format!(
+            "<degree><degree-value>{}</degree-value><degree-alter>{}</degree-alter><degree-type>add</degree-type></degree>",
+            degree, alter
+        );
+    }
+    for degree in chord.added() {
+        ans += &format!(
+            "<degree><degree-value>{}</degree-value><degree-alter>0</degree-alter><degree-type>add</degree-type></degree>",
+            degree
+        );
+    }
+    for &(degree, shift) in chord
+        .altered()
+        .iter()
+        .filter(|(degree, _)| !extra.contains(degree))
+    {
+        // tensions above the chord are added rather than altered
+        let degree_type = if degree.div_ceil(2) <= chord.note_num() {
+            "alter"
+        } else {
+            "add"
+        };
+        ans += &format!(
+            "<degree><degree-value>{}</degree-value><degree-alter>{}</degree-alter><degree-type>{}</degree-type></degree>",
+            degree, shift, degree_type
+        );
+    }
+    for degree in chord
+        .omitted()
+        .iter()
+        .filter(|degree| !extra.contains(degree))
+    {
+        ans += &format!(
+            "<degree><degree-value>{}</degree-value><degree-alter>0</degree-alter><degree-type>subtract</degree-type></degree>",
+            degree
+        );
+    }
+    ans + "</harmony>"
+}
+
+/// MusicXML `<note>` elements of rests lasting `sixteenths` sixteenth notes in total, split into
+/// rests of standard lengths.
+fn rests(mut sixteenths: u32) -> String {
+    let mut ans = String::new();
+    for &(len, rest_type, dotted) in RESTS {
+        while sixteenths >= len {
+            ans += &format!(
+                "      <note><rest/><duration>{}</duration><type>{}</type>{}</note>\n",
+                len,
+                rest_type,
+                if dotted { "<dot/>" } else { "" }
+            );
+            sixteenths -= len;
+        }
+    }
+    ans
+}
+
+/// Write the chord sequence as a minimal MusicXML score in `key` with tempo `tempo` BPM. The score
+/// has a single part in 4/4, with each chord symbol over rests lasting `durations[i]` beats, or a
+/// whole measure if `durations` is shorter than `chords`. Durations are rounded to sixteenth notes,
+/// and chords are held over bar lines if needed.
+pub fn to_musicxml(chords: &[Chord], durations: &[f32], key: Key, tempo: u32) -> String {
+    let mut ans = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n",
+        "<!DOCTYPE score-partwise PUBLIC \"-//Recordare//DTD MusicXML 4.0 Partwise//EN\" ",
+        "\"http://www.musicxml.org/dtds/partwise.dtd\">\n",
+        "<score-partwise version=\"4.0\">\n",
+        "  <part-list>\n",
+        "    <score-part id=\"P1\"><part-name>Chords</part-name></score-part>\n",
+        "  </part-list>\n",
+        "  <part id=\"P1\">\n",
+    ));
+    for (i, bar) in rhythm::bars(chords, durations).iter().enumerate() {
+        ans += &format!("    <measure number=\"{}\">\n", i + 1);
+        if i == 0 {
+            ans += &format!(
+                concat!(
+                    "      <attributes><divisions>4</divisions>",
+                    "<key><fifths>{}</fifths><mode>{}</mode></key>",
+                    "<time><beats>4</beats><beat-type>4</beat-type></time>",
+                    "<clef><sign>G</sign><line>2</line></clef></attributes>\n",
+                    "      <direction placement=\"above\"><direction-type><metronome>",
+                    "<beat-unit>quarter</beat-unit><per-minute>{}</per-minute>",
+                    "</metronome></direction-type><sound tempo=\"{}\"/></direction>\n",
+                ),
+                fifths(key),
+                if key.is_major() { "major" } else { "minor" },
+                tempo,
+                tempo
+            );
+        }
+        for &(chord, len) in bar {
+            if let Some(chord) = chord {
+                ans += &format!("      {}\n", harmony(chord, key));
+            }
+            ans += &rests(len);
+        }
+        ans += "    </measure>\n";
+    }
+    ans += "  </part>\n</score-partwise>\n";
+    ans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Check that every tag is closed in order, ignoring the XML declaration and doctype.
+    fn is_well_formed(xml: &str) -> bool {
+        let mut stack = Vec::new();
+        let mut rest = xml;
+        while let Some(start) = rest.find('<') {
+            let end = match rest[start..].find('>') {
+                Some(end) => start + end,
+                None => return false,
+            };
+            let tag = &rest[start + 1..end];
+            rest = &rest[end + 1..];
+            if tag.starts_with('?') || tag.starts_with('!') || tag.ends_with('/') {
+                continue;
+            }
+            if let Some(name) = tag.strip_prefix('/') {
+                if stack.pop() != Some(name) {
+                    return false;
+                }
+            } else {
+                stack.push(tag.split_whitespace().next().unwrap_or(""));
+            }
+        }
+        stack.is_empty()
+    }
+
+    #[test]
+    fn test_to_musicxml() {
+        let chords = [
+            Chord::try_from("Dm7").unwrap(),
+            Chord::try_from("G7").unwrap(),
+            Chord::try_from("CM7/E").unwrap(),
+            Chord::try_from("Bø7").unwrap(),
+        ];
+        let xml = to_musicxml(&chords, &[], Key::major(3), 90);
+        assert!(is_well_formed(&xml));
+        assert_eq!(xml.matches("<measure ").count(), 4);
+        assert_eq!(xml.matches("<harmony>").count(), 4);
+        assert!(xml.contains("<fifths>0</fifths>"));
+        assert!(xml.contains("<per-minute>90</per-minute>"));
+        assert!(xml.contains("<root-step>D</root-step></root><kind>minor-seventh</kind>"));
+        assert!(xml.contains("<kind>dominant</kind>"));
+        assert!(xml.contains("<kind>major-seventh</kind><bass><bass-step>E</bass-step></bass>"));
+        assert!(xml.contains("<kind>half-diminished</kind>"));
+    }
+
+    #[test]
+    fn test_durations() {
+        let chords = [
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("G").unwrap(),
+            Chord::try_from("Am").unwrap(),
+        ];
+        let xml = to_musicxml(&chords, &[2.0, 3.0, 1.5], Key::major(3), 120);
+        assert!(is_well_formed(&xml));
+        assert!(xml.contains("<divisions>4</divisions>"));
+        assert_eq!(xml.matches("<measure ").count(), 2);
+        assert_eq!(xml.matches("<harmony>").count(), 3);
+        let total: u32 = xml
+            .split("<duration>")
+            .skip(1)
+            .map(|rest| rest[..rest.find('<').unwrap()].parse::<u32>().unwrap())
+            .sum();
+        assert_eq!(total, 32);
+        assert!(xml.contains("<duration>6</duration><type>quarter</type><dot/>"));
+        assert_eq!(
+            rests(16),
+            "      <note><rest/><duration>16</duration><type>whole</type></note>\n"
+        );
+        assert_eq!(rests(5).matches("<note>").count(), 2);
+    }
+
+    #[test]
+    fn test_key() {
+        let b_flat = Chord::try_from("Bbadd9").unwrap();
+        let xml = to_musicxml(&[b_flat], &[], Key::major(8), 120);
+        assert!(is_well_formed(&xml));
+        assert!(xml.contains("<fifths>-1</fifths><mode>major</mode>"));
+        assert!(xml.contains("<root-step>B</root-step><root-alter>-1</root-alter>"));
+        assert!(xml.contains("<degree-value>9</degree-value>"));
+        let xml = to_musicxml(
+            &[Chord::try_from("C7#5b9").unwrap()],
+            &[],
+            Key::major(3),
+            120,
+        );
+        assert!(is_well_formed(&xml));
+        assert!(xml.contains("<degree-value>5</degree-value><degree-alter>1</degree-alter><degree-type>alter</degree-type>"));
+        assert!(xml.contains("<degree-value>9</degree-value><degree-alter>-1</degree-alter><degree-type>add</degree-type>"));
+        let xml = to_musicxml(
+            &[Chord::try_from("C7(no5)").unwrap()],
+            &[],
+            Key::major(3),
+            120,
+        );
+        assert!(is_well_formed(&xml));
+        assert!(xml.contains("<degree-value>5</degree-value><degree-alter>0</degree-alter><degree-type>subtract</degree-type>"));
+        assert_eq!(fifths(Key::minor(7)), 1);
+        assert_eq!(fifths(Key::major(4)), -5);
+        assert_eq!(fifths(Key::major(9)), 6);
+        assert_eq!(kind(&Chord::try_from("C5").unwrap()), ("power", 2));
+    }
+
+    #[test]
+    fn test_extra_degrees() {
+        // chord tones above the kind are added, so that no tone is lost
+        for (name, kind_name, degrees) in [
+            ("C7sus4", "suspended-fourth", vec![(7, 0)]),
+            ("C9sus2", "suspended-second", vec![(7, 0), (9, 0)]),
+            ("Caug9", "augmented-seventh", vec![(9, 2)]),
+            ("Cdim9", "diminished-seventh", vec![(9, -2)]),
+            ("C9sus4b9", "suspended-fourth", vec![(7, 0), (9, -1)]),
+            ("C13", "dominant-13th", vec![]),
+        ] {
+            let chord = Chord::try_from(name).unwrap();
+            let (kind, kind_note_num) = kind(&chord);
+            assert_eq!(kind, kind_name);
+            assert_eq!(extra_degrees(&chord), degrees);
+            assert_eq!(kind_note_num as usize + degrees.len(), chord.notes().len());
+            let xml = harmony(&chord, Key::major(3));
+            assert_eq!(xml.matches("<degree-type>add").count(), degrees.len());
+            assert!(!xml.contains("<degree-type>alter"));
+        }
+        let xml = harmony(&Chord::try_from("C9sus4(no5)").unwrap(), Key::major(3));
+        assert!(xml.contains("<degree-value>5</degree-value><degree-alter>0</degree-alter><degree-type>subtract</degree-type>"));
+        assert_eq!(xml.matches("<degree-type>add").count(), 2);
+    }
+}
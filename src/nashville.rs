@@ -0,0 +1,105 @@
+use crate::{chord::Chord, key::Key, note::Note};
+
+/// Nashville number of `note` in `key`, i.e. its scale degree as an arabic numeral. Notes outside
+/// the scale are written as the degree above lowered by a flat, e.g. "b7" for Bb in C major, except
+/// that the notes of the parallel major scale in a minor key are written as the degree below raised
+/// by a sharp, e.g. "#7" for the leading tone G# in A minor.
+fn number(note: Note, key: Key) -> String {
+    let interval = (note + 12 - key.tonic()) % 12;
+    let degree_of = |interval: Note| {
+        key.scale_intervals()
+            .iter()
+            .position(|&rel| rel == interval % 12)
+            .map(|degree| degree + 1)
+    };
+    if let Some(degree) = degree_of(interval) {
+        return degree.to_string();
+    }
+    let flat = degree_of(interval + 1).map(|degree| format!("b{}", degree));
+    let sharp = degree_of(interval + 11).map(|degree| format!("#{}", degree));
+    let raised = !key.is_major()
+        && Key::major(key.tonic())
+            .scale_intervals()
+            .contains(&interval);
+    if raised {
+        sharp.or(flat)
+    } else {
+        flat.or(sharp)
+    }
+    .expect("notes outside a diatonic scale lie between two notes of the scale")
+}
+
+/// Convert each chord to its Nashville number in `key`, e.g. "1", "4", "5", "6m" for C F G Am in
+/// C major. The quality is written after the number as in chord names, and the bass note of a
+/// slash chord is also written as a number, e.g. "1/3" for C/E in C major. Extensions right after
+/// the number are separated by "^" so that they are not read as part of it, e.g. "5^7" for G7 and
+/// "4^9" for F9 in C major.
+pub fn to_nashville(chords: &[Chord], key: Key) -> Vec<String> {
+    chords
+        .iter()
+        .map(|chord| {
+            let bass = chord.bass().map(|bass| number(bass, key));
+            let root = number(chord.root(), key);
+            let name = chord.name_with("", bass.as_deref());
+            if name.starts_with(|ch: char| ch.is_ascii_digit()) {
+                format!("{}^{}", root, name)
+            } else {
+                root + &name
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chords(names: &[&str]) -> Vec<Chord> {
+        names
+            .iter()
+            .map(|name| Chord::try_from(*name).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_to_nashville() {
+        let c_major = Key::major(3);
+        assert_eq!(
+            to_nashville(&chords(&["C", "F", "G", "Am"]), c_major),
+            ["1", "4", "5", "6m"]
+        );
+        assert_eq!(
+            to_nashville(&chords(&["Dm7", "G7", "CM7/E", "Bb", "Eb", "Bø7"]), c_major),
+            ["2m7", "5^7", "1M7/3", "b7", "b3", "7ø7"]
+        );
+        assert_eq!(
+            to_nashville(&chords(&["Am", "Dm", "E7"]), Key::minor(0)),
+            ["1m", "4m", "5^7"]
+        );
+    }
+
+    #[test]
+    fn test_to_nashville_extended() {
+        let c_major = Key::major(3);
+        assert_eq!(
+            to_nashville(
+                &chords(&["F9", "G13", "C5", "C6", "G7sus4", "Dm9", "Bb9/D"]),
+                c_major
+            ),
+            ["4^9", "5^13", "1^5", "1^6", "5^7sus4", "2m9", "b7^9/2"]
+        );
+    }
+
+    #[test]
+    fn test_to_nashville_minor_chromatic() {
+        let a_minor = Key::minor(0);
+        assert_eq!(
+            to_nashville(&chords(&["G#dim", "A/C#", "F#m", "E7/G#", "Bb"]), a_minor),
+            ["#7dim", "1/#3", "#6m", "5^7/#7", "b2"]
+        );
+        assert_eq!(
+            to_nashville(&chords(&["Ebdim", "F#"]), a_minor),
+            ["b5dim", "#6"]
+        );
+    }
+}
@@ -1,44 +1,137 @@
-use crate::error::Result;
+use crate::error::{Error, Result};
 
 pub type Note = u8; // 0 as note 'A' or roman numeral 'I'
 
+/// Accidentals and the semitones they raise a note by. Double accidentals are listed before single
+/// ones so that both characters are consumed.
+const ACCIDENTALS: [(&str, i8); 10] = [
+    ("##", 2),
+    ("x", 2),
+    ("𝄪", 2),
+    ("bb", -2),
+    ("𝄫", -2),
+    ("#", 1),
+    ("♯", 1),
+    ("b", -1),
+    ("♭", -1),
+    ("♮", 0),
+];
+
+/// Convention of note names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NoteConvention {
+    /// English note names, where B is B natural.
+    #[default]
+    English,
+    /// German and Scandinavian note names, where H is B natural and B is B flat.
+    German,
+    /// Fixed Do solfège syllables, where Do is C. See `consume_to_note_solfege`.
+    Solfege,
+}
+
+/// Solfège syllables in fixed Do and their notes.
+const SOLFEGE: [(&str, Note); 8] = [
+    ("Do", 3),
+    ("Re", 5),
+    ("Mi", 7),
+    ("Fa", 8),
+    ("Sol", 10),
+    ("La", 0),
+    ("Si", 2),
+    ("Ti", 2),
+];
+
+/// Characters used for accidentals when spelling notes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AccidentalStyle {
+    /// ASCII "#" and "b", e.g. "A#" and "Bb".
+    #[default]
+    Ascii,
+    /// Unicode "♯" and "♭", e.g. "A♯" and "B♭".
+    Unicode,
+}
+
+/// Note `delta` semitones above `note`, or below if `delta` is negative, in modulo 12. Notes are
+/// always wrapped into 0..12 without overflowing, e.g. 1 semitone below A (0) is G# (11).
+pub fn add_semitones(note: Note, delta: i8) -> Note {
+    (note as i16 + delta as i16).rem_euclid(12) as Note
+}
+
 /// Read a note from a string slice. Returns the note number, key (major = true, minor = false), and
 /// the position just after the note.
-pub(crate) fn consume_to_note(str: &str) -> Result<(Note, bool, &str)> {
-    let mut index = 0;
-    if let Some(note_char) = str.chars().nth(index) {
-        let mut note = match note_char {
-            'A' => Ok(0),
-            'B' => Ok(2),
-            'C' => Ok(3),
-            'D' => Ok(5),
-            'E' => Ok(7),
-            'F' => Ok(8),
-            'G' => Ok(10),
-            _ => Err(format!("Invalid note character: {}", note_char)),
+pub fn consume_to_note(str: &str) -> Result<(Note, bool, &str)> {
+    consume_to_note_with_convention(str, NoteConvention::English)
+}
+
+/// Read an optional accidental from a string slice. Returns the semitones it raises a note by and
+/// the position just after the accidental.
+fn consume_accidental(str: &str) -> (i8, &str) {
+    ACCIDENTALS
+        .iter()
+        .find_map(|&(symbol, shift)| str.strip_prefix(symbol).map(|rest| (shift, rest)))
+        .unwrap_or((0, str))
+}
+
+/// Read a note from a string slice like `consume_to_note`, with note names in `convention`.
+pub fn consume_to_note_with_convention(
+    str: &str,
+    convention: NoteConvention,
+) -> Result<(Note, bool, &str)> {
+    if convention == NoteConvention::Solfege {
+        return consume_to_note_solfege(str);
+    }
+    if let Some(note_char) = str.chars().next() {
+        let note = match (note_char, convention) {
+            ('A', _) => Ok(0),
+            ('B', NoteConvention::English) | ('H', NoteConvention::German) => Ok(2),
+            ('B', NoteConvention::German) => Ok(1),
+            ('C', _) => Ok(3),
+            ('D', _) => Ok(5),
+            ('E', _) => Ok(7),
+            ('F', _) => Ok(8),
+            ('G', _) => Ok(10),
+            _ => Err(Error::InvalidNote(note_char)),
         }?;
         let key = note_char.is_uppercase();
-        index += 1;
-        match str.chars().nth(index) {
-            Some('#') | Some('♯') => {
-                index += 1;
-                note += 1;
-            }
-            Some('b') | Some('♭') => {
-                index += 1;
-                note -= 1;
-            }
-            Some('♮') => {
-                index += 1;
-            }
-            _ => {}
-        }
-        Ok((note, key, &str[index..]))
+        let (shift, str_next) = consume_accidental(&str[note_char.len_utf8()..]);
+        Ok((add_semitones(note, shift), key, str_next))
     } else {
-        Err("Invalid note format".to_string())
+        Err(Error::InvalidNoteFormat)
     }
 }
 
+/// Read a note written as a solfège syllable in fixed Do (Do is C) from a string slice like
+/// `consume_to_note`, e.g. "Do", "Sol#" or "Sib". Both "Si" and "Ti" are B.
+pub fn consume_to_note_solfege(str: &str) -> Result<(Note, bool, &str)> {
+    let note_char = str.chars().next().ok_or(Error::InvalidNoteFormat)?;
+    let (note, str_next) = SOLFEGE
+        .iter()
+        .find_map(|&(syllable, note)| str.strip_prefix(syllable).map(|rest| (note, rest)))
+        .ok_or(Error::InvalidNote(note_char))?;
+    let (shift, str_next) = consume_accidental(str_next);
+    Ok((add_semitones(note, shift), true, str_next))
+}
+
+/// Read a note with an octave from a string slice, e.g. "C4" or "Bb-1". Returns the MIDI note number
+/// of the pitch (see `midi_number`) and the position just after the octave.
+///
+/// This is for bare notes rather than chords, since the digits after a chord root are the chord
+/// size, e.g. "C7" is the pitch C7 here but a seventh chord in `Chord::try_from`.
+pub fn consume_to_pitch(str: &str) -> Result<(i32, &str)> {
+    let (note, _, str_next) = consume_to_note(str)?;
+    let sign_len = usize::from(str_next.starts_with('-'));
+    let octave_len = str_next[sign_len..]
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(str_next.len() - sign_len);
+    if octave_len == 0 {
+        return Err(Error::InvalidNoteFormat);
+    }
+    let (octave_str, str_next) = str_next.split_at(sign_len + octave_len);
+    let octave = octave_str.parse().map_err(|_| Error::InvalidNoteFormat)?;
+    let midi = checked_midi_number(note, octave).ok_or(Error::InvalidNoteFormat)?;
+    Ok((midi, str_next))
+}
+
 pub fn note_string(note: Note) -> Vec<&'static str> {
     match note % 12 {
         0 => vec!["A"],
@@ -56,3 +149,264 @@ pub fn note_string(note: Note) -> Vec<&'static str> {
         _ => vec![],
     }
 }
+
+/// Spellings of `note` like `note_string`, with accidentals in `style`.
+pub fn note_string_with(note: Note, style: AccidentalStyle) -> Vec<&'static str> {
+    match (style, note % 12) {
+        (AccidentalStyle::Unicode, 1) => vec!["A♯", "B♭"],
+        (AccidentalStyle::Unicode, 4) => vec!["C♯", "D♭"],
+        (AccidentalStyle::Unicode, 6) => vec!["D♯", "E♭"],
+        (AccidentalStyle::Unicode, 9) => vec!["F♯", "G♭"],
+        (AccidentalStyle::Unicode, 11) => vec!["G♯", "A♭"],
+        _ => note_string(note),
+    }
+}
+
+/// Number of steps between two notes around the circle of fifths, from 0 (same note) to 6 (tritone).
+/// Notes a fifth apart in either direction, e.g. G and C, are 1 step apart.
+pub fn fifths_distance(a: Note, b: Note) -> u8 {
+    let steps = (b as i32 - a as i32).rem_euclid(12) * 7 % 12;
+    steps.min(12 - steps) as u8
+}
+
+/// MIDI note number of `note` in `octave`. Octaves follow scientific pitch notation, which starts
+/// from note C, so note A (0) in octave 4 is A4 (MIDI 69) and note C (3) in octave 4 is middle C
+/// (MIDI 60).
+pub fn midi_number(note: Note, octave: i32) -> i32 {
+    12 * (octave + 1) + ((note as i32 + 9) % 12)
+}
+
+/// MIDI note number of `note` in `octave` like `midi_number`, or `None` if it overflows `i32`.
+fn checked_midi_number(note: Note, octave: i32) -> Option<i32> {
+    octave
+        .checked_add(1)?
+        .checked_mul(12)?
+        .checked_add((note as i32 + 9) % 12)
+}
+
+/// Ratios of the 12 notes above the root in 5-limit just intonation.
+const JUST_RATIOS: [f32; 12] = [
+    1.0,
+    16.0 / 15.0,
+    9.0 / 8.0,
+    6.0 / 5.0,
+    5.0 / 4.0,
+    4.0 / 3.0,
+    45.0 / 32.0,
+    3.0 / 2.0,
+    8.0 / 5.0,
+    5.0 / 3.0,
+    9.0 / 5.0,
+    15.0 / 8.0,
+];
+
+/// Temperament of a tuning.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Temperament {
+    /// Twelve-tone equal temperament.
+    #[default]
+    Equal,
+    /// 5-limit just intonation relative to `root`, e.g. the tonic of the key.
+    Just { root: Note },
+}
+
+/// Tuning used to compute frequencies of notes, i.e. the concert pitch and the temperament.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tuning {
+    pub a4_hz: f32,
+    pub temperament: Temperament,
+}
+
+impl Default for Tuning {
+    /// A4 tuned to the standard 440 Hz in equal temperament.
+    fn default() -> Self {
+        Self {
+            a4_hz: 440.0,
+            temperament: Temperament::Equal,
+        }
+    }
+}
+
+/// Frequency in Hz of `note` in `octave` in `tuning`. See `midi_number` for how octaves are
+/// numbered.
+///
+/// In equal temperament A4 is tuned to `tuning.a4_hz`. In just intonation, the root is tuned as in
+/// equal temperament, and the other notes are tuned by their ratio to the root below them, so A4 is
+/// only tuned to `tuning.a4_hz` if the root is A.
+pub fn frequency(note: Note, octave: i32, tuning: Tuning) -> f32 {
+    let midi = midi_number(note, octave);
+    let equal = |midi: i32| tuning.a4_hz * 2f32.powf((midi - 69) as f32 / 12.0);
+    match tuning.temperament {
+        Temperament::Equal => equal(midi),
+        Temperament::Just { root } => {
+            let interval = (note as i32 - root as i32).rem_euclid(12);
+            equal(midi - interval) * JUST_RATIOS[interval as usize]
+        }
+    }
+}
+
+/// Frequency in Hz of `note` in `octave`, with A4 tuned to the standard 440 Hz in equal temperament.
+pub fn frequency_standard(note: Note, octave: i32) -> f32 {
+    frequency(note, octave, Tuning::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_consume_to_note_with_convention() {
+        let input = "Bm7";
+        assert_eq!(consume_to_note(input), Ok((2, true, "m7")));
+        assert_eq!(
+            consume_to_note_with_convention(input, NoteConvention::English),
+            Ok((2, true, "m7"))
+        );
+        assert_eq!(
+            consume_to_note_with_convention(input, NoteConvention::German),
+            Ok((1, true, "m7"))
+        );
+        let input = "H7";
+        assert_eq!(consume_to_note(input), Err(Error::InvalidNote('H')));
+        assert_eq!(
+            consume_to_note_with_convention(input, NoteConvention::German),
+            Ok((2, true, "7"))
+        );
+    }
+
+    #[test]
+    fn test_add_semitones() {
+        assert_eq!(add_semitones(3, 4), 7);
+        assert_eq!(add_semitones(11, 1), 0);
+        assert_eq!(add_semitones(10, 21), 7);
+        assert_eq!(add_semitones(0, -1), 11);
+        assert_eq!(add_semitones(3, -15), 0);
+        assert_eq!(add_semitones(0, i8::MIN), 4);
+        assert_eq!(add_semitones(255, i8::MAX), 10);
+        assert_eq!(consume_to_note("Ab"), Ok((11, true, "")));
+        assert_eq!(consume_to_note("Abb"), Ok((10, true, "")));
+        assert_eq!(consume_to_note("G#"), Ok((11, true, "")));
+    }
+
+    #[test]
+    fn test_consume_to_note_solfege() {
+        assert_eq!(consume_to_note_solfege("Do"), Ok((3, true, "")));
+        assert_eq!(consume_to_note_solfege("La"), Ok((0, true, "")));
+        assert_eq!(consume_to_note_solfege("Solm7"), Ok((10, true, "m7")));
+        assert_eq!(consume_to_note_solfege("Sib"), Ok((1, true, "")));
+        assert_eq!(consume_to_note_solfege("Ti"), consume_to_note("B"));
+        assert_eq!(consume_to_note_solfege("Fa♯m"), Ok((9, true, "m")));
+        assert_eq!(consume_to_note_solfege("Mi"), consume_to_note("E"));
+        assert_eq!(consume_to_note_solfege("Re"), consume_to_note("D"));
+        assert_eq!(consume_to_note_solfege("C"), Err(Error::InvalidNote('C')));
+        assert_eq!(consume_to_note_solfege(""), Err(Error::InvalidNoteFormat));
+        assert_eq!(
+            consume_to_note_with_convention("Dom7", NoteConvention::Solfege),
+            Ok((3, true, "m7"))
+        );
+    }
+
+    #[test]
+    fn test_double_accidentals() {
+        assert_eq!(consume_to_note("Cx"), Ok((5, true, "")));
+        assert_eq!(consume_to_note("C##m7"), Ok((5, true, "m7")));
+        assert_eq!(consume_to_note("Dbb"), Ok((3, true, "")));
+        assert_eq!(consume_to_note("Abbm"), Ok((10, true, "m")));
+        assert_eq!(consume_to_note("G𝄪"), Ok((0, true, "")));
+        assert_eq!(consume_to_note("E𝄫7"), Ok((5, true, "7")));
+        assert_eq!(consume_to_note("B#"), Ok((3, true, "")));
+    }
+
+    #[test]
+    fn test_unicode_accidentals() {
+        assert_eq!(consume_to_note("C♭"), consume_to_note("Cb"));
+        assert_eq!(consume_to_note("E♯m"), Ok((8, true, "m")));
+        assert_eq!(consume_to_note("B♮7"), Ok((2, true, "7")));
+        assert_eq!(consume_to_note("♯"), Err(Error::InvalidNote('♯')));
+        assert_eq!(consume_to_note("bm"), Err(Error::InvalidNote('b')));
+        assert_eq!(note_string_with(1, AccidentalStyle::Ascii), ["A#", "Bb"]);
+        assert_eq!(note_string_with(1, AccidentalStyle::Unicode), ["A♯", "B♭"]);
+        assert_eq!(note_string_with(3, AccidentalStyle::Unicode), ["C"]);
+        for note in 0..12 {
+            for name in note_string_with(note, AccidentalStyle::Unicode) {
+                assert_eq!(consume_to_note(name), Ok((note, true, "")));
+            }
+        }
+    }
+
+    #[test]
+    fn test_consume_to_pitch() {
+        assert_eq!(consume_to_pitch("C4"), Ok((60, "")));
+        assert_eq!(consume_to_pitch("A4 C5"), Ok((69, " C5")));
+        assert_eq!(consume_to_pitch("Bb3"), Ok((58, "")));
+        assert_eq!(consume_to_pitch("C-1"), Ok((0, "")));
+        assert_eq!(consume_to_pitch("C7"), Ok((96, "")));
+        assert_eq!(consume_to_pitch("C"), Err(Error::InvalidNoteFormat));
+        assert_eq!(consume_to_pitch("Cm7"), Err(Error::InvalidNoteFormat));
+        assert_eq!(consume_to_pitch("C-"), Err(Error::InvalidNoteFormat));
+        assert_eq!(
+            consume_to_pitch("C999999999"),
+            Err(Error::InvalidNoteFormat)
+        );
+        assert_eq!(
+            consume_to_pitch("C-999999999"),
+            Err(Error::InvalidNoteFormat)
+        );
+        assert_eq!(
+            consume_to_pitch("C99999999999"),
+            Err(Error::InvalidNoteFormat)
+        );
+    }
+
+    #[test]
+    fn test_fifths_distance() {
+        assert_eq!(fifths_distance(3, 3), 0);
+        assert_eq!(fifths_distance(10, 3), 1);
+        assert_eq!(fifths_distance(3, 10), 1);
+        assert_eq!(fifths_distance(3, 5), 2);
+        assert_eq!(fifths_distance(3, 0), 3);
+        assert_eq!(fifths_distance(3, 4), 5);
+        assert_eq!(fifths_distance(3, 9), 6);
+    }
+
+    #[test]
+    fn test_midi_number() {
+        assert_eq!(midi_number(0, 4), 69);
+        assert_eq!(midi_number(3, 4), 60);
+        assert_eq!(midi_number(2, 3), 59);
+        assert_eq!(midi_number(3, -1), 0);
+    }
+
+    #[test]
+    fn test_frequency() {
+        assert_eq!(frequency_standard(0, 4), 440.0);
+        assert_eq!(frequency_standard(0, 5), 880.0);
+        let baroque = Tuning {
+            a4_hz: 415.0,
+            ..Default::default()
+        };
+        assert_eq!(frequency(0, 4, baroque), 415.0);
+        assert!((frequency_standard(3, 4) - 261.626).abs() < 1e-2);
+        assert!((frequency_standard(7, 4) * 2.0 - frequency_standard(7, 5)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_just_intonation() {
+        let equal = Tuning::default();
+        let just = Tuning {
+            temperament: Temperament::Just { root: 3 },
+            ..equal
+        };
+        // major third C4 to E4
+        let equal_third = frequency(7, 4, equal) / frequency(3, 4, equal);
+        let just_third = frequency(7, 4, just) / frequency(3, 4, just);
+        assert!((equal_third - 2f32.powf(4.0 / 12.0)).abs() < 1e-5);
+        assert!((just_third - 1.25).abs() < 1e-5);
+        assert!(just_third < equal_third);
+        // the root is tuned as in equal temperament
+        assert_eq!(frequency(3, 4, just), frequency(3, 4, equal));
+        // major sixth C4 to A4, and B3 a major seventh above C3
+        assert!((frequency(0, 4, just) / frequency(3, 4, just) - 5.0 / 3.0).abs() < 1e-5);
+        assert!((frequency(2, 3, just) / frequency(3, 3, just) - 15.0 / 8.0).abs() < 1e-5);
+    }
+}
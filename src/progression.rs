@@ -0,0 +1,217 @@
+use std::fmt::Display;
+
+use crate::{
+    chord::Chord,
+    error::{Error, Result},
+};
+
+/// Error of a single chord token in a progression, located by its 1-based line and column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenError {
+    pub line: usize,
+    pub column: usize,
+    pub token: String,
+    pub error: Error,
+}
+
+impl Display for TokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: cannot parse chord {}: {}",
+            self.line, self.column, self.token, self.error
+        )
+    }
+}
+
+/// Whether the character separates chords in a progression.
+fn is_separator(ch: char) -> bool {
+    ch.is_whitespace() || ch == ',' || ch == '|'
+}
+
+/// Parse a chord progression, e.g. the content of a chord file.
+///
+/// Chords are separated by any whitespace, `,` or `|`, and lines beginning with `#` are comments.
+/// If any chord cannot be parsed, returns `Error::InvalidProgression` with the errors of all such
+/// chords instead of stopping at the first one.
+pub fn parse_progression(input: &str) -> Result<Vec<Chord>> {
+    let mut chords = Vec::new();
+    let mut errors = Vec::new();
+    for (line_index, line) in input.lines().enumerate() {
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+        let mut token_start = None;
+        // a separator is appended so that the last token of the line is also handled
+        for (column, (byte_index, ch)) in line.char_indices().chain([(line.len(), ' ')]).enumerate()
+        {
+            match (token_start, is_separator(ch)) {
+                (None, false) => token_start = Some((column, byte_index)),
+                (Some((start_column, start_byte)), true) => {
+                    let token = &line[start_byte..byte_index];
+                    match Chord::try_from(token) {
+                        Ok(chord) => chords.push(chord),
+                        Err(error) => errors.push(TokenError {
+                            line: line_index + 1,
+                            column: start_column + 1,
+                            token: token.to_string(),
+                            error,
+                        }),
+                    }
+                    token_start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+    if errors.is_empty() {
+        Ok(chords)
+    } else {
+        Err(Error::InvalidProgression(errors))
+    }
+}
+
+/// Parse the chords of a ChordPro song, i.e. the chord symbols in brackets within the lyrics, e.g.
+/// `[C]Twinkle twinkle [G7]little star`.
+///
+/// Lyrics are ignored, and so are directive lines in braces, e.g. `{title: Song}`, and lines
+/// beginning with `#`. Errors are returned like `parse_progression`, located at the start of the
+/// chord symbol after the bracket. An unclosed bracket is `Error::InvalidFormat`.
+pub fn parse_chordpro(input: &str) -> Result<Vec<Chord>> {
+    let mut chords = Vec::new();
+    let mut errors = Vec::new();
+    for (line_index, line) in input.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('#') || trimmed.starts_with('{') {
+            continue;
+        }
+        let mut rest = line;
+        while let Some(open) = rest.find('[') {
+            let start_byte = line.len() - rest.len() + open + 1;
+            let column = line[..start_byte].chars().count() + 1;
+            let (token, parsed) = match rest[open + 1..].find(']') {
+                Some(close) => {
+                    let token = &rest[open + 1..open + 1 + close];
+                    rest = &rest[open + close + 2..];
+                    (token, Chord::try_from(token.trim()))
+                }
+                None => {
+                    let token = &rest[open + 1..];
+                    rest = "";
+                    (token, Err(Error::InvalidFormat))
+                }
+            };
+            match parsed {
+                Ok(chord) => chords.push(chord),
+                Err(error) => errors.push(TokenError {
+                    line: line_index + 1,
+                    column,
+                    token: token.to_string(),
+                    error,
+                }),
+            }
+        }
+    }
+    if errors.is_empty() {
+        Ok(chords)
+    } else {
+        Err(Error::InvalidProgression(errors))
+    }
+}
+
+/// Format a chord progression with `sep` between chords, e.g. `" "`, `", "` or `" | "`. The result
+/// is parsed back to the same chords by `parse_progression` if `sep` only contains separators.
+pub fn format_progression(chords: &[Chord], sep: &str) -> String {
+    chords
+        .iter()
+        .map(|chord| chord.to_string())
+        .collect::<Vec<_>>()
+        .join(sep)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_progression() {
+        let input = "# verse\nC\tG | Am,F\n\n  # chorus: F G C\nF G\r\nC  ,, |\n";
+        let expected: Vec<Chord> = ["C", "G", "Am", "F", "F", "G", "C"]
+            .into_iter()
+            .map(|s| Chord::try_from(s).unwrap())
+            .collect();
+        assert_eq!(parse_progression(input).unwrap(), expected);
+        assert_eq!(parse_progression("# only a comment\n\n").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_format_progression() {
+        let chords = parse_progression("C G Am7 F/A").unwrap();
+        assert_eq!(format_progression(&chords, " | "), "C | G | Am7 | F/A");
+        assert_eq!(format_progression(&chords, ", "), "C, G, Am7, F/A");
+        assert_eq!(format_progression(&chords[..1], " | "), "C");
+        assert_eq!(format_progression(&[], " "), "");
+        assert_eq!(
+            parse_progression(&format_progression(&chords, " | ")).unwrap(),
+            chords
+        );
+    }
+
+    #[test]
+    fn test_parse_chordpro() {
+        let input = concat!(
+            "{title: Twinkle Twinkle}\n",
+            "# a comment with [Dm]\n",
+            "[C]Twinkle twinkle [F]little [C]star\n",
+            "\n",
+            "[F]How I [C]wonder [G7]what you [C]are\n",
+            "{c: [Am] in a directive}\n",
+            "Up above the world so high [ Am7/G ]\n",
+        );
+        let expected: Vec<Chord> = ["C", "F", "C", "F", "C", "G7", "C", "Am7/G"]
+            .into_iter()
+            .map(|s| Chord::try_from(s).unwrap())
+            .collect();
+        assert_eq!(parse_chordpro(input).unwrap(), expected);
+        assert_eq!(parse_chordpro("no chords here").unwrap(), vec![]);
+        assert_eq!(
+            parse_chordpro("[C]la [Xm]la\nla [G").unwrap_err(),
+            Error::InvalidProgression(vec![
+                TokenError {
+                    line: 1,
+                    column: 8,
+                    token: "Xm".to_string(),
+                    error: Error::InvalidNote('X'),
+                },
+                TokenError {
+                    line: 2,
+                    column: 5,
+                    token: "G".to_string(),
+                    error: Error::InvalidFormat,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_progression_errors() {
+        let input = "C Xm G\n# Ym\nAm, F7x\n";
+        assert_eq!(
+            parse_progression(input).unwrap_err(),
+            Error::InvalidProgression(vec![
+                TokenError {
+                    line: 1,
+                    column: 3,
+                    token: "Xm".to_string(),
+                    error: Error::InvalidNote('X'),
+                },
+                TokenError {
+                    line: 3,
+                    column: 5,
+                    token: "F7x".to_string(),
+                    error: Chord::try_from("F7x").unwrap_err(),
+                },
+            ])
+        );
+    }
+}
@@ -0,0 +1,59 @@
+#![allow(non_snake_case)]
+
+use crate::{chord::Chord, key::Key};
+
+/// Resolve a progression of roman numerals in `key`.
+fn resolve(numerals: &[&str], key: Key) -> Vec<Chord> {
+    numerals
+        .iter()
+        .map(|numeral| Chord::try_from_roman(numeral, key).expect("valid roman numeral"))
+        .collect()
+}
+
+/// The "axis" progression I–V–vi–IV of countless pop songs.
+pub fn I_V_vi_IV(key: Key) -> Vec<Chord> {
+    resolve(&["I", "V", "vi", "IV"], key)
+}
+
+/// The ii7–V7–IM7 progression, the most common cadence in jazz.
+pub fn ii_V_I(key: Key) -> Vec<Chord> {
+    resolve(&["ii7", "V7", "IM7"], key)
+}
+
+/// The twelve-bar blues with dominant seventh chords and a turnaround in the last bar, one chord
+/// per bar.
+pub fn twelve_bar_blues(key: Key) -> Vec<Chord> {
+    resolve(
+        &[
+            "I7", "I7", "I7", "I7", "IV7", "IV7", "I7", "I7", "V7", "IV7", "I7", "V7",
+        ],
+        key,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(chords: &[Chord]) -> Vec<String> {
+        chords.iter().map(|chord| chord.to_string()).collect()
+    }
+
+    #[test]
+    fn test_I_V_vi_IV() {
+        assert_eq!(names(&I_V_vi_IV(Key::major(3))), ["C", "G", "Am", "F"]);
+    }
+
+    #[test]
+    fn test_ii_V_I() {
+        assert_eq!(names(&ii_V_I(Key::major(3))), ["Dm7", "G7", "CM7"]);
+    }
+
+    #[test]
+    fn test_twelve_bar_blues() {
+        assert_eq!(
+            names(&twelve_bar_blues(Key::major(3))),
+            ["C7", "C7", "C7", "C7", "F7", "F7", "C7", "C7", "G7", "F7", "C7", "G7"]
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use crate::chord::Chord;
+
+/// Duration in beats of chords whose duration is not given, i.e. a whole note in 4/4.
+pub const DEFAULT_DURATION: f32 = 4.0;
+
+/// Number of sixteenth notes in a bar of 4/4.
+pub const BAR_SIXTEENTHS: u32 = 16;
+
+/// Duration in beats of chord `index`, or `DEFAULT_DURATION` if `durations` is shorter.
+pub fn duration(durations: &[f32], index: usize) -> f32 {
+    durations.get(index).copied().unwrap_or(DEFAULT_DURATION)
+}
+
+/// Duration of chord `index` rounded to sixteenth notes, and at least one sixteenth note.
+pub fn sixteenths(durations: &[f32], index: usize) -> u32 {
+    ((duration(durations, index) * 4.0).round() as u32).max(1)
+}
+
+/// Split the chords into bars of 4/4 as `(chord, sixteenths)` pairs, where `chord` is the chord
+/// starting at that point, or `None` where the previous chord is held over the bar line or where
+/// the last bar is padded.
+pub fn bars<'a>(chords: &'a [Chord], durations: &[f32]) -> Vec<Vec<(Option<&'a Chord>, u32)>> {
+    let mut ans = vec![];
+    let mut bar = vec![];
+    let mut filled = 0;
+    for (i, chord) in chords.iter().enumerate() {
+        let mut chord = Some(chord);
+        let mut rest = sixteenths(durations, i);
+        while rest > 0 {
+            let len = rest.min(BAR_SIXTEENTHS - filled);
+            bar.push((chord.take(), len));
+            rest -= len;
+            filled += len;
+            if filled == BAR_SIXTEENTHS {
+                ans.push(std::mem::take(&mut bar));
+                filled = 0;
+            }
+        }
+    }
+    if filled > 0 {
+        bar.push((None, BAR_SIXTEENTHS - filled));
+        ans.push(bar);
+    }
+    ans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bars() {
+        let chords = [
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("G").unwrap(),
+            Chord::try_from("Am").unwrap(),
+        ];
+        let (c, g, am) = (Some(&chords[0]), Some(&chords[1]), Some(&chords[2]));
+        assert_eq!(bars(&chords, &[]), [[(c, 16)], [(g, 16)], [(am, 16)]]);
+        assert_eq!(
+            bars(&chords, &[2.0, 3.0, 1.5]),
+            [vec![(c, 8), (g, 8)], vec![(None, 4), (am, 6), (None, 6)]]
+        );
+        assert_eq!(sixteenths(&[0.0], 0), 1);
+        assert_eq!(sixteenths(&[0.3], 0), 1);
+    }
+}
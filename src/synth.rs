@@ -0,0 +1,166 @@
+use std::{
+    f32::consts::PI,
+    io::{Result, Seek, SeekFrom, Write},
+};
+
+use crate::{
+    chord::Chord,
+    note::{frequency, midi_number, Tuning},
+};
+
+/// Length in seconds of the fade in and fade out of each chord, which avoids clicks between chords.
+const FADE_SECONDS: f32 = 0.005;
+
+/// Options of WAV rendering.
+#[derive(Debug, Clone, Copy)]
+pub struct SynthOptions {
+    pub sample_rate: u32, // samples per second
+    pub duration: f32,    // seconds of each chord
+    pub amplitude: f32,   // peak amplitude of each chord, from 0.0 to 1.0
+    pub octave: i32,      // octave of the lowest note of each chord
+    pub tuning: Tuning,   // tuning of the frequencies of notes
+}
+
+impl Default for SynthOptions {
+    /// Default options play one chord per second at 44.1 kHz, with the lowest note in octave 3.
+    fn default() -> Self {
+        Self {
+            sample_rate: 44100,
+            duration: 1.0,
+            amplitude: 0.5,
+            octave: 3,
+            tuning: Tuning::default(),
+        }
+    }
+}
+
+/// Frequencies of the notes of a chord in ascending order, with the first note of the chord placed
+/// in `octave`.
+fn chord_frequencies(chord: &Chord, octave: i32, tuning: Tuning) -> Vec<f32> {
+    let mut ans = Vec::new();
+    let mut last = None;
+    for note in chord.notes() {
+        let mut note_octave = octave;
+        while last.is_some_and(|last| midi_number(note, note_octave) <= last) {
+            note_octave += 1;
+        }
+        last = Some(midi_number(note, note_octave));
+        ans.push(frequency(note, note_octave, tuning));
+    }
+    ans
+}
+
+/// Write a little-endian chunk size at `position` and return to the end of the output.
+fn patch_size<W: Write + Seek>(out: &mut W, position: u64, size: u32) -> Result<()> {
+    let end = out.stream_position()?;
+    out.seek(SeekFrom::Start(position))?;
+    out.write_all(&size.to_le_bytes())?;
+    out.seek(SeekFrom::Start(end))?;
+    Ok(())
+}
+
+/// Render the chord sequence as a mono 16-bit PCM WAV file, e.g. to preview a progression without a
+/// MIDI player. Each chord is played for `opts.duration` seconds as the sum of sine waves of its
+/// notes, normalized to `opts.amplitude`.
+///
+/// The sizes in the header are written after the samples, so `out` is seeked back to them.
+pub fn render_wav<W: Write + Seek>(
+    chords: &[Chord],
+    out: &mut W,
+    opts: SynthOptions,
+) -> Result<()> {
+    let start = out.stream_position()?;
+    out.write_all(b"RIFF")?;
+    out.write_all(&0u32.to_le_bytes())?; // size of the RIFF chunk, patched at the end
+    out.write_all(b"WAVE")?;
+    out.write_all(b"fmt ")?;
+    out.write_all(&16u32.to_le_bytes())?;
+    out.write_all(&1u16.to_le_bytes())?; // PCM
+    out.write_all(&1u16.to_le_bytes())?; // mono
+    out.write_all(&opts.sample_rate.to_le_bytes())?;
+    out.write_all(&(opts.sample_rate * 2).to_le_bytes())?; // bytes per second
+    out.write_all(&2u16.to_le_bytes())?; // bytes per sample
+    out.write_all(&16u16.to_le_bytes())?; // bits per sample
+    out.write_all(b"data")?;
+    out.write_all(&0u32.to_le_bytes())?; // size of the data chunk, patched at the end
+
+    let rate = opts.sample_rate as f32;
+    let samples_per_chord = (opts.duration.max(0.0) * rate).round() as usize;
+    let fade = ((FADE_SECONDS * rate) as usize).clamp(1, samples_per_chord.max(1));
+    let amplitude = opts.amplitude.clamp(0.0, 1.0) * i16::MAX as f32;
+    let mut data = Vec::with_capacity(chords.len() * samples_per_chord * 2);
+    for chord in chords {
+        let frequencies = chord_frequencies(chord, opts.octave, opts.tuning);
+        let gain = amplitude / frequencies.len().max(1) as f32;
+        for i in 0..samples_per_chord {
+            let t = i as f32 / rate;
+            let envelope = (i.min(samples_per_chord - 1 - i) as f32 / fade as f32).min(1.0);
+            let value: f32 = frequencies.iter().map(|f| (2.0 * PI * f * t).sin()).sum();
+            data.extend(((value * gain * envelope) as i16).to_le_bytes());
+        }
+    }
+    out.write_all(&data)?;
+
+    patch_size(out, start + 4, 36 + data.len() as u32)?;
+    patch_size(out, start + 40, data.len() as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn read_u32(bytes: &[u8], index: usize) -> u32 {
+        u32::from_le_bytes(bytes[index..index + 4].try_into().unwrap())
+    }
+
+    fn read_u16(bytes: &[u8], index: usize) -> u16 {
+        u16::from_le_bytes(bytes[index..index + 2].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_render_wav() {
+        let chords = [
+            Chord::try_from("C").unwrap(),
+            Chord::try_from("G7").unwrap(),
+            Chord::try_from("Am").unwrap(),
+        ];
+        let opts = SynthOptions {
+            sample_rate: 8000,
+            duration: 0.5,
+            ..Default::default()
+        };
+        let mut out = Cursor::new(Vec::new());
+        render_wav(&chords, &mut out, opts).unwrap();
+        let bytes = out.into_inner();
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(read_u32(&bytes, 4) as usize, bytes.len() - 8);
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!(read_u16(&bytes, 20), 1);
+        assert_eq!(read_u16(&bytes, 22), 1);
+        assert_eq!(read_u32(&bytes, 24), 8000);
+        assert_eq!(read_u32(&bytes, 28), 16000);
+        assert_eq!(read_u16(&bytes, 34), 16);
+        assert_eq!(&bytes[36..40], b"data");
+        let data_len = read_u32(&bytes, 40);
+        assert_eq!(data_len as usize, bytes.len() - 44);
+        // 3 chords of 0.5 seconds, 2 bytes per sample
+        let seconds = data_len as f32 / 2.0 / 8000.0;
+        assert_eq!(seconds, 1.5);
+        // the first chord is not silent
+        assert!(bytes[44..8044]
+            .chunks(2)
+            .any(|sample| i16::from_le_bytes([sample[0], sample[1]]).abs() > 1000));
+    }
+
+    #[test]
+    fn test_chord_frequencies() {
+        let c = Chord::try_from("C/E").unwrap();
+        let frequencies = chord_frequencies(&c, 4, Tuning::default());
+        assert_eq!(frequencies.len(), 3);
+        assert!(frequencies.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(frequencies[0], frequency(7, 4, Tuning::default()));
+        assert_eq!(frequencies[1], frequency(3, 5, Tuning::default()));
+    }
+}
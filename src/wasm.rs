@@ -0,0 +1,56 @@
+//! JavaScript bindings of the generator, behind the `wasm` feature. The crate is built as a
+//! normal library, so build the WebAssembly module as a `cdylib` from the command line:
+//!
+//! ```text
+//! cargo rustc --lib --release --target wasm32-unknown-unknown --features wasm --crate-type cdylib
+//! ```
+
+use wasm_bindgen::prelude::*;
+
+use crate::{chord::Chord, generator::ChordGenerator, progression::parse_progression};
+
+/// Chord generator exported to JavaScript, trained from a chord progression.
+#[wasm_bindgen]
+pub struct WasmGenerator {
+    generator: ChordGenerator,
+}
+
+#[wasm_bindgen]
+impl WasmGenerator {
+    /// Train a generator from a chord progression in the format of `parse_progression`. Throws an
+    /// exception if any chord cannot be parsed.
+    #[wasm_bindgen(constructor)]
+    pub fn new(corpus: &str) -> Result<WasmGenerator, JsError> {
+        let chord_seq = parse_progression(corpus)?;
+        Ok(Self {
+            generator: ChordGenerator::new(&chord_seq),
+        })
+    }
+
+    /// Generate `n` chords following the chord `init` with a random number generator seeded with
+    /// `seed`, separated by spaces. Throws an exception if `init` cannot be parsed or is not in the
+    /// training set.
+    pub fn generate(&self, init: &str, n: usize, seed: u64) -> Result<String, JsError> {
+        let init_chord = Chord::try_from(init)?;
+        let generated = self.generator.generate_seeded(init_chord, n, seed)?;
+        Ok(generated
+            .iter()
+            .map(|chord| chord.to_string())
+            .collect::<Vec<_>>()
+            .join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        // errors are only converted to exceptions on wasm targets, so only success is tested here
+        let generator = WasmGenerator::new("C G Am F\nC G F C").unwrap();
+        let generated = generator.generate("C", 8, 42).unwrap();
+        assert_eq!(generated.split(' ').count(), 8);
+        assert_eq!(generated, generator.generate("C", 8, 42).unwrap());
+    }
+}